@@ -0,0 +1,72 @@
+/*
+Program Details: Blackjack table server for the local network
+
+Hosts one table that several players can sit at from their own computers.
+The server deals and keeps everybody's chips, the players join it with the
+game itself:
+
+    cargo run --release --bin server -- [options]
+    cargo run --release -- --join 192.168.1.20:7777
+
+To try it on one machine start the server, then run the game a few times with
+--join 127.0.0.1:7777 and a different profile in each window.
+
+Options (defaults in brackets):
+    --port N            port to listen on [7777]
+    --decks N           decks in the shoe [6]
+    --s17 / --h17       dealer stands or hits soft 17 [h17]
+    --seed N            shoe seed [clock]
+*/
+use std::env;
+use std::process::exit;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use blackjack3::game::net::DEFAULT_PORT;
+use blackjack3::game::rules::TableRules;
+use blackjack3::game::server::serve;
+
+fn main() {
+    let mut port = DEFAULT_PORT;
+    let mut rules = TableRules::default();
+    let mut seed = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs());
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--s17" => rules.dealer_hits_soft_17 = false,
+            "--h17" => rules.dealer_hits_soft_17 = true,
+            "--help" | "-h" => usage(""),
+            "--port" | "--decks" | "--seed" => {
+                let Some(value) = args.next() else {
+                    usage(&format!("{} needs a value", arg));
+                };
+                match arg.as_str() {
+                    "--port" => port = parse_value(&arg, &value),
+                    "--decks" => rules.decks = parse_value::<usize>(&arg, &value).clamp(1, 8),
+                    _ => seed = parse_value(&arg, &value),
+                }
+            }
+            _ => usage(&format!("unknown option {}", arg)),
+        }
+    }
+
+    println!("shoe seed {}", seed);
+    // Every address on this machine, so other computers on the network can reach it
+    if let Err(err) = serve(&format!("0.0.0.0:{}", port), rules, seed) {
+        eprintln!("server: could not open port {}: {}", port, err);
+        exit(1);
+    }
+}
+
+fn parse_value<T: FromStr>(arg: &str, value: &str) -> T {
+    value.parse().unwrap_or_else(|_| usage(&format!("bad value for {}: {}", arg, value)))
+}
+
+fn usage(problem: &str) -> ! {
+    if !problem.is_empty() {
+        eprintln!("server: {}", problem);
+    }
+    eprintln!("usage: server [--port N] [--decks N] [--s17|--h17] [--seed N]");
+    exit(if problem.is_empty() { 0 } else { 2 });
+}
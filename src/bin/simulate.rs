@@ -0,0 +1,433 @@
+/*
+Program Details: Headless blackjack simulator
+
+Plays millions of rounds with the same engine the GUI uses (no window, no
+drawing) and reports how the house does: house edge, variance, risk of ruin
+and how often hands win, push, lose and get blackjack. With basic strategy the
+edge can be checked against published numbers for the same rules.
+
+Run it with:
+    cargo run --release --bin simulate -- [options]
+
+Options (defaults in brackets):
+    --rounds N          rounds to play [1000000]
+    --strategy S        basic, dealer (mimic the dealer) or never-bust [basic]
+    --betting B         flat, martingale or paroli [flat]
+    --unit N            chips in one betting unit [100]
+    --bankroll N        units each session starts with, for risk of ruin [100]
+    --session N         rounds in a session [1000]
+    --decks N           decks in the shoe, 1 to 8 [6]
+    --s17 / --h17       dealer stands or hits soft 17 [h17]
+    --payout P          blackjack payout: 3:2, 6:5 or 1:1 [3:2]
+    --no-das            no doubling after a split
+    --rsa               split aces can be split again
+    --surrender S       none, late or early [late]
+    --penetration F     how much of the shoe is dealt before the shuffle [0.75]
+    --seed N            shoe seed [clock]
+
+Example:
+    cargo run --release --bin simulate -- --rounds 5000000 --decks 2 --s17 --payout 6:5
+*/
+use std::env;
+use std::process::exit;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use blackjack3::game::engine::{BlackjackGame, Outcome, Phase};
+use blackjack3::game::limits::TableLimits;
+use blackjack3::game::rules::{BlackjackPayout, Surrender, TableRules};
+use blackjack3::game::shoe::{MAX_DECKS, MIN_DECKS};
+use blackjack3::game::strategy::{advise, Play};
+
+// How the player decides what to do with a hand
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Strategy {
+    Basic,
+    MimicDealer,
+    NeverBust,
+}
+
+impl Strategy {
+    fn choose(self, game: &BlackjackGame) -> Play {
+        let Some(active) = game.active_hand() else {
+            return Play::Stand;
+        };
+        let hand = &game.hands()[active].hand;
+        match self {
+            Strategy::Basic => advise(game).unwrap_or(Play::Stand),
+            Strategy::MimicDealer => {
+                let total = hand.best_total();
+                if total < 17 || (total == 17 && hand.is_soft() && game.rules().dealer_hits_soft_17) {
+                    Play::Hit
+                } else {
+                    Play::Stand
+                }
+            }
+            // Only hits when no card could bust the hand
+            Strategy::NeverBust => {
+                if hand.hard_total() <= 11 {
+                    Play::Hit
+                } else {
+                    Play::Stand
+                }
+            }
+        }
+    }
+}
+
+// How much to bet each round
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Betting {
+    Flat,
+    // Double the bet after every loss, back to one unit after a win
+    Martingale,
+    // Double the bet after every win, back to one unit after a loss or three wins in a row
+    Paroli,
+}
+
+struct Settings {
+    rounds: u64,
+    strategy: Strategy,
+    betting: Betting,
+    unit: u32,
+    bankroll_units: u32,
+    session: u64,
+    rules: TableRules,
+    seed: u64,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let seed = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        Self {
+            rounds: 1_000_000,
+            strategy: Strategy::Basic,
+            betting: Betting::Flat,
+            unit: 100,
+            bankroll_units: 100,
+            session: 1000,
+            // Progressions run away from any table maximum, the simulator plays without limits
+            rules: TableRules {
+                limits: TableLimits::NONE,
+                ..Default::default()
+            },
+            seed,
+        }
+    }
+}
+
+// Everything counted while playing, money is in chips
+#[derive(Default)]
+struct Totals {
+    rounds: u64,
+    hands: u64,
+    wins: u64,
+    pushes: u64,
+    losses: u64,
+    blackjacks: u64,
+    surrenders: u64,
+    busts: u64,
+    doubles: u64,
+    splits: u64,
+    initial_bets: i64,
+    total_staked: i64,
+    net: i64,
+    // Running mean and sum of squares of the net per round in units (Welford's method)
+    mean: f64,
+    m2: f64,
+    sessions: u64,
+    ruined: u64,
+}
+
+impl Totals {
+    fn add_round(&mut self, game: &BlackjackGame, initial_bet: u32, unit: u32) {
+        self.rounds += 1;
+        for hand in game.hands() {
+            self.hands += 1;
+            if hand.doubled {
+                self.doubles += 1;
+            }
+            if hand.from_split {
+                self.splits += 1;
+            }
+            match hand.outcome {
+                Some(Outcome::Blackjack) => {
+                    self.wins += 1;
+                    self.blackjacks += 1;
+                }
+                Some(Outcome::Win) => self.wins += 1,
+                Some(Outcome::Push) => self.pushes += 1,
+                Some(Outcome::Lose) => self.losses += 1,
+                Some(Outcome::Bust) => {
+                    self.losses += 1;
+                    self.busts += 1;
+                }
+                Some(Outcome::Surrender) => {
+                    self.losses += 1;
+                    self.surrenders += 1;
+                }
+                None => {}
+            }
+        }
+
+        let net = game.total_payout() as i64 - game.total_staked() as i64;
+        self.initial_bets += initial_bet as i64;
+        self.total_staked += game.total_staked() as i64;
+        self.net += net;
+
+        let units = net as f64 / unit as f64;
+        let delta = units - self.mean;
+        self.mean += delta / self.rounds as f64;
+        self.m2 += delta * (units - self.mean);
+    }
+
+    fn variance(&self) -> f64 {
+        if self.rounds < 2 {
+            0.0
+        } else {
+            self.m2 / (self.rounds - 1) as f64
+        }
+    }
+}
+
+fn main() {
+    let settings = parse_args();
+    let unit = settings.unit;
+    // parse_args made sure this fits
+    let bankroll = settings.unit * settings.bankroll_units;
+    let mut game = BlackjackGame::new(settings.rules.clone(), bankroll, settings.seed);
+    let mut totals = Totals::default();
+
+    let mut played = 0;
+    while played < settings.rounds {
+        // Every session starts over with the same bankroll, going broke counts towards risk of ruin
+        game.set_bankroll(bankroll);
+        totals.sessions += 1;
+        let mut bet = unit;
+        let mut wins_in_a_row = 0;
+        let mut session_rounds = 0;
+
+        while session_rounds < settings.session && played < settings.rounds {
+            if game.bankroll() < unit {
+                totals.ruined += 1;
+                break;
+            }
+            let stake = bet.min(game.bankroll());
+            play_round(&mut game, stake, settings.strategy);
+            totals.add_round(&game, stake, unit);
+            played += 1;
+            session_rounds += 1;
+
+            let net = game.total_payout() as i64 - game.total_staked() as i64;
+            bet = next_bet(settings.betting, bet, unit, net, &mut wins_in_a_row);
+            game.next_round().expect("round is settled");
+        }
+    }
+
+    report(&settings, &totals);
+}
+
+// Plays one whole round, insurance is always turned down
+fn play_round(game: &mut BlackjackGame, bet: u32, strategy: Strategy) {
+    game.place_bet(bet).expect("bet fits the bankroll");
+    game.deal().expect("cards are dealt after a bet");
+    loop {
+        match game.phase() {
+            Phase::Insurance => {
+                game.decline_insurance().ok();
+            }
+            // Early surrender against a ten, only basic strategy ever takes it
+            Phase::Dealing => {
+                if strategy == Strategy::Basic && advise(game) == Some(Play::Surrender) {
+                    game.surrender().ok();
+                } else {
+                    game.decline_surrender().ok();
+                }
+            }
+            Phase::PlayerTurn => {
+                let result = match strategy.choose(game) {
+                    Play::Hit => game.hit(),
+                    Play::Stand => game.stand(),
+                    Play::Double => game.double(),
+                    Play::Split => game.split(),
+                    Play::Surrender => game.surrender(),
+                };
+                // Anything the table does not allow right now (like hitting split aces) becomes a stand
+                if result.is_err() {
+                    game.stand().ok();
+                }
+            }
+            Phase::DealerTurn => {
+                game.play_dealer().ok();
+            }
+            Phase::Settlement => return,
+            Phase::Betting => unreachable!("round already started"),
+        }
+    }
+}
+
+fn next_bet(betting: Betting, bet: u32, unit: u32, net: i64, wins_in_a_row: &mut u32) -> u32 {
+    match betting {
+        Betting::Flat => unit,
+        Betting::Martingale => {
+            if net < 0 {
+                bet.saturating_mul(2)
+            } else if net > 0 {
+                unit
+            } else {
+                bet
+            }
+        }
+        Betting::Paroli => {
+            if net > 0 {
+                *wins_in_a_row += 1;
+                if *wins_in_a_row >= 3 {
+                    *wins_in_a_row = 0;
+                    unit
+                } else {
+                    bet.saturating_mul(2)
+                }
+            } else if net < 0 {
+                *wins_in_a_row = 0;
+                unit
+            } else {
+                bet
+            }
+        }
+    }
+}
+
+fn report(settings: &Settings, totals: &Totals) {
+    let rounds = totals.rounds.max(1) as f64;
+    let hands = totals.hands.max(1) as f64;
+    let percent = |count: u64| 100.0 * count as f64 / hands;
+
+    // Published house edges are measured against the first bet of the round
+    let edge = -100.0 * totals.net as f64 / totals.initial_bets.max(1) as f64;
+    let std_dev = totals.variance().sqrt();
+    let error = 100.0 * std_dev / rounds.sqrt();
+
+    println!("Rules:      {}", settings.rules);
+    println!(
+        "Strategy:   {:?}, betting: {:?}, unit {} chips, seed {}",
+        settings.strategy, settings.betting, settings.unit, settings.seed
+    );
+    println!("Rounds:     {} ({} hands)", totals.rounds, totals.hands);
+    println!();
+    println!("House edge: {:.3}% (+/- {:.3}%)", edge, error);
+    println!("Net:        {:.1} units", totals.net as f64 / settings.unit as f64);
+    println!(
+        "Action:     {:.3} units staked per round",
+        totals.total_staked as f64 / settings.unit as f64 / rounds
+    );
+    println!("Variance:   {:.3} units^2 per round (std dev {:.3})", totals.variance(), std_dev);
+    println!(
+        "Ruin:       {:.2}% of {} sessions ({} unit bankroll, {} rounds each)",
+        100.0 * totals.ruined as f64 / totals.sessions.max(1) as f64,
+        totals.sessions,
+        settings.bankroll_units,
+        settings.session
+    );
+    println!();
+    println!("Wins:       {:.2}%", percent(totals.wins));
+    println!("Pushes:     {:.2}%", percent(totals.pushes));
+    println!("Losses:     {:.2}%", percent(totals.losses));
+    println!("Blackjacks: {:.2}%", percent(totals.blackjacks));
+    println!(
+        "Busts {:.2}%, surrenders {:.2}%, doubles {:.2}%, split hands {:.2}%",
+        percent(totals.busts),
+        percent(totals.surrenders),
+        percent(totals.doubles),
+        percent(totals.splits)
+    );
+}
+
+fn parse_args() -> Settings {
+    let mut settings = Settings::default();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--s17" => settings.rules.dealer_hits_soft_17 = false,
+            "--h17" => settings.rules.dealer_hits_soft_17 = true,
+            "--no-das" => settings.rules.double_after_split = false,
+            "--rsa" => settings.rules.resplit_aces = true,
+            "--help" | "-h" => usage(""),
+            _ => {
+                let Some(value) = args.next() else {
+                    usage(&format!("{} needs a value", arg));
+                };
+                match arg.as_str() {
+                    "--rounds" => settings.rounds = parse_value(&arg, &value),
+                    "--unit" => settings.unit = parse_value(&arg, &value),
+                    "--bankroll" => settings.bankroll_units = parse_value(&arg, &value),
+                    "--session" => settings.session = parse_value(&arg, &value),
+                    "--decks" => settings.rules.decks = parse_value(&arg, &value),
+                    "--penetration" => settings.rules.penetration = parse_value(&arg, &value),
+                    "--seed" => settings.seed = parse_value(&arg, &value),
+                    "--strategy" => {
+                        settings.strategy = match value.as_str() {
+                            "basic" => Strategy::Basic,
+                            "dealer" => Strategy::MimicDealer,
+                            "never-bust" => Strategy::NeverBust,
+                            _ => bad_value(&arg, &value),
+                        }
+                    }
+                    "--betting" => {
+                        settings.betting = match value.as_str() {
+                            "flat" => Betting::Flat,
+                            "martingale" => Betting::Martingale,
+                            "paroli" => Betting::Paroli,
+                            _ => bad_value(&arg, &value),
+                        }
+                    }
+                    "--payout" => {
+                        settings.rules.blackjack_payout = match value.as_str() {
+                            "3:2" => BlackjackPayout::ThreeToTwo,
+                            "6:5" => BlackjackPayout::SixToFive,
+                            "1:1" => BlackjackPayout::EvenMoney,
+                            _ => bad_value(&arg, &value),
+                        }
+                    }
+                    "--surrender" => {
+                        settings.rules.surrender = match value.as_str() {
+                            "none" => Surrender::None,
+                            "late" => Surrender::Late,
+                            "early" => Surrender::Early,
+                            _ => bad_value(&arg, &value),
+                        }
+                    }
+                    _ => usage(&format!("unknown option {}", arg)),
+                }
+            }
+        }
+    }
+    if settings.unit == 0 || settings.bankroll_units == 0 || settings.session == 0 {
+        usage("--unit, --bankroll and --session must be more than 0");
+    }
+    if settings.unit.checked_mul(settings.bankroll_units).is_none() {
+        usage("--unit times --bankroll is more chips than a bankroll can hold");
+    }
+    // The shoe would quietly clamp the decks, so say so instead
+    if !(MIN_DECKS..=MAX_DECKS).contains(&settings.rules.decks) {
+        usage(&format!("--decks must be {} to {}", MIN_DECKS, MAX_DECKS));
+    }
+    settings
+}
+
+fn parse_value<T: FromStr>(arg: &str, value: &str) -> T {
+    value.parse().unwrap_or_else(|_| bad_value(arg, value))
+}
+
+fn bad_value(arg: &str, value: &str) -> ! {
+    usage(&format!("bad value for {}: {}", arg, value))
+}
+
+fn usage(problem: &str) -> ! {
+    if !problem.is_empty() {
+        eprintln!("simulate: {}", problem);
+    }
+    eprintln!("usage: simulate [--rounds N] [--strategy basic|dealer|never-bust] [--betting flat|martingale|paroli]");
+    eprintln!("                [--unit N] [--bankroll N] [--session N] [--decks N] [--s17|--h17] [--payout 3:2|6:5|1:1]");
+    eprintln!("                [--no-das] [--rsa] [--surrender none|late|early] [--penetration F] [--seed N]");
+    exit(if problem.is_empty() { 0 } else { 2 });
+}
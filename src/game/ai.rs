@@ -0,0 +1,149 @@
+/*
+Program Details: Computer players for the other seats at the table
+
+In your game/mod.rs file add the following:
+    pub mod ai;
+
+Then with the other use commands add:
+use blackjack3::game::ai::{AiPlayer, AiStyle};
+
+Usage examples:
+    let mut bot = AiPlayer::new(AiStyle::Counter, "Sam", 10, game.shoe().decks(), seed);
+
+    // Every bot sees every card, feed it the events from each game action
+    bot.observe(&events);
+
+    // Betting, insurance and playing, only ask when it is the bot's seat
+    game.place_bet_at(seat, bot.bet(&game, seat))?;
+    if bot.wants_insurance(&game) { ... }
+    if let Some(play) = bot.play(&game) { ... }
+
+Styles:
+    Basic    flat bets, plays basic strategy
+    Counter  counts Hi-Lo, bets 1 to 8 units as the true count goes up and
+             takes insurance at a true count of +3 or more
+    Hunch    bets 1 to 4 units on a whim and plays on gut feeling
+*/
+use std::fmt;
+
+use macroquad::rand::RandGenerator;
+use serde::{Deserialize, Serialize};
+
+use crate::game::counting::{CardCounter, HiLo};
+use crate::game::engine::{BlackjackGame, GameEvent};
+use crate::game::strategy::{advise, Play};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AiStyle {
+    Basic,
+    Counter,
+    Hunch,
+}
+
+impl fmt::Display for AiStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AiStyle::Basic => write!(f, "Basic"),
+            AiStyle::Counter => write!(f, "Counter"),
+            AiStyle::Hunch => write!(f, "Hunch"),
+        }
+    }
+}
+
+/// Who sits in a seat at the table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SeatPlayer {
+    Human,
+    Ai(AiStyle),
+}
+
+impl fmt::Display for SeatPlayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SeatPlayer::Human => write!(f, "You"),
+            SeatPlayer::Ai(style) => write!(f, "{}", style),
+        }
+    }
+}
+
+/// A computer player, it keeps its own count and random numbers
+pub struct AiPlayer {
+    pub style: AiStyle,
+    pub name: String,
+    unit: u32,
+    counter: CardCounter,
+    rng: RandGenerator,
+}
+
+impl AiPlayer {
+    /// `unit` is the smallest bet it makes
+    pub fn new(style: AiStyle, name: &str, unit: u32, decks: usize, seed: u64) -> Self {
+        let rng = RandGenerator::new();
+        rng.srand(seed);
+        Self {
+            style,
+            name: name.to_string(),
+            unit: unit.max(1),
+            counter: CardCounter::new(Box::new(HiLo), decks),
+            rng,
+        }
+    }
+
+    pub fn observe(&mut self, events: &[GameEvent]) {
+        self.counter.observe(events);
+    }
+
+    fn true_count(&self, game: &BlackjackGame) -> f32 {
+        self.counter.true_count(game.shoe().decks_remaining())
+    }
+
+    /// What it bets on the next round from the seat's bankroll, kept inside the table limits. Short of the minimum it cannot bet and sits out
+    pub fn bet(&self, game: &BlackjackGame, seat: usize) -> u32 {
+        let bankroll = game.seats().get(seat).map_or(0, |player| player.bankroll() + player.bet());
+        let units = match self.style {
+            AiStyle::Basic => 1,
+            // One unit until the count is good, then a unit more for each true count above +1
+            AiStyle::Counter => (self.true_count(game).floor() as i32).clamp(1, 8) as u32,
+            AiStyle::Hunch => self.rng.gen_range(1, 5),
+        };
+        game.rules().limits.clamp(units * self.unit).min(bankroll)
+    }
+
+    /// Whether to take insurance (or even money) when the dealer shows an ace
+    pub fn wants_insurance(&self, game: &BlackjackGame) -> bool {
+        match self.style {
+            AiStyle::Basic => false,
+            // Insurance is worth it once a third of the cards left are tens, about a true count of +3
+            AiStyle::Counter => self.true_count(game) >= 3.0,
+            AiStyle::Hunch => self.rng.gen_range(0, 4) == 0,
+        }
+    }
+
+    /// The play for the active hand, None when it is not anybody's turn
+    pub fn play(&self, game: &BlackjackGame) -> Option<Play> {
+        match self.style {
+            AiStyle::Basic | AiStyle::Counter => advise(game),
+            AiStyle::Hunch => self.hunch(game),
+        }
+    }
+
+    // Doubles 10 and 11 half the time, splits any pair now and then and
+    // stops hitting somewhere between 13 and 17
+    fn hunch(&self, game: &BlackjackGame) -> Option<Play> {
+        let seat = game.active_seat()?;
+        let hand = game.active_hand()?;
+        let total = game.seats()[seat].hands()[hand].hand.best_total();
+        if game.can_double() && (10..=11).contains(&total) && self.rng.gen_range(0, 2) == 0 {
+            return Some(Play::Double);
+        }
+        if game.can_split() && self.rng.gen_range(0, 3) == 0 {
+            return Some(Play::Split);
+        }
+        let stop = self.rng.gen_range(13, 18);
+        if total >= stop || !game.can_hit() {
+            Some(Play::Stand)
+        } else {
+            Some(Play::Hit)
+        }
+    }
+}
@@ -0,0 +1,311 @@
+/*
+Program Details: Card, Rank and Suit types for the blackjack game
+
+In your game/mod.rs file add the following:
+    pub mod card;
+
+Then with the other use commands add:
+use blackjack3::game::card::{Card, Rank, Suit};
+
+Usage examples:
+    let card = Card::new(Rank::Ace, Suit::Hearts);
+    card.rank.value();          // 1 (a Hand decides when an ace counts as 11)
+    card.asset_path();          // "assets/aceHeart.png"
+    card.to_string();           // "AH"
+    let parsed: Card = "10s".parse().unwrap();
+
+To get every card image (plus the back) for the TextureManager:
+    tm.preload_with_loading_screen(&Card::all_asset_paths(), None).await;
+
+Cards are written as a rank followed by a suit letter: A, 2-10 (or T), J, Q, K
+and C, D, H, S. Parsing ignores case.
+*/
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// Image shown for a face down card
+pub const CARD_BACK: &str = "assets/backcard.png";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Suit {
+    Clubs,
+    Diamonds,
+    Hearts,
+    Spades,
+}
+
+impl Suit {
+    pub const ALL: [Suit; 4] = [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades];
+
+    // Position of the suit in Suit::ALL
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    // Single letter used by Display and FromStr
+    pub fn letter(self) -> char {
+        match self {
+            Suit::Clubs => 'C',
+            Suit::Diamonds => 'D',
+            Suit::Hearts => 'H',
+            Suit::Spades => 'S',
+        }
+    }
+
+    pub fn is_red(self) -> bool {
+        matches!(self, Suit::Diamonds | Suit::Hearts)
+    }
+}
+
+impl fmt::Display for Suit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.letter())
+    }
+}
+
+impl FromStr for Suit {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "C" => Ok(Suit::Clubs),
+            "D" => Ok(Suit::Diamonds),
+            "H" => Ok(Suit::Hearts),
+            "S" => Ok(Suit::Spades),
+            _ => Err(ParseCardError::BadSuit(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Rank {
+    Ace,
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Ten,
+    Jack,
+    Queen,
+    King,
+}
+
+impl Rank {
+    pub const ALL: [Rank; 13] = [
+        Rank::Ace,
+        Rank::Two,
+        Rank::Three,
+        Rank::Four,
+        Rank::Five,
+        Rank::Six,
+        Rank::Seven,
+        Rank::Eight,
+        Rank::Nine,
+        Rank::Ten,
+        Rank::Jack,
+        Rank::Queen,
+        Rank::King,
+    ];
+
+    // Position of the rank in Rank::ALL
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Blackjack value of the rank. Aces count as 1 here, a Hand works out
+    /// when one of them can be counted as 11 instead.
+    pub fn value(self) -> u8 {
+        match self {
+            Rank::Jack | Rank::Queen | Rank::King => 10,
+            _ => self.index() as u8 + 1,
+        }
+    }
+
+    pub fn is_ace(self) -> bool {
+        self == Rank::Ace
+    }
+
+    // Symbol used by Display (A, 2-10, J, Q, K)
+    pub fn symbol(self) -> &'static str {
+        const SYMBOLS: [&str; 13] = ["A", "2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K"];
+        SYMBOLS[self.index()]
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+impl FromStr for Rank {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rank = match s.trim().to_ascii_uppercase().as_str() {
+            "A" | "1" => Rank::Ace,
+            "T" => Rank::Ten,
+            "J" => Rank::Jack,
+            "Q" => Rank::Queen,
+            "K" => Rank::King,
+            other => match other.parse::<usize>() {
+                Ok(n) if (2..=10).contains(&n) => Rank::ALL[n - 1],
+                _ => return Err(ParseCardError::BadRank(s.to_string())),
+            },
+        };
+        Ok(rank)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Card {
+    pub rank: Rank,
+    pub suit: Suit,
+}
+
+// Texture file for every card, indexed by [rank][suit] in the same order as Rank::ALL and Suit::ALL
+#[rustfmt::skip]
+const ASSET_PATHS: [[&str; 4]; 13] = [
+    ["assets/aceClubs.png", "assets/aceDiamond.png", "assets/aceHeart.png", "assets/aceSpade.png"],
+    ["assets/02clubs.png", "assets/02diamond.png", "assets/02heart.png", "assets/02spade.png"],
+    ["assets/03clubs.png", "assets/03diamond.png", "assets/03heart.png", "assets/03spade.png"],
+    ["assets/04clubs.png", "assets/04diamond.png", "assets/04heart.png", "assets/04spade.png"],
+    ["assets/05clubs.png", "assets/05diamond.png", "assets/05heart.png", "assets/05spade.png"],
+    ["assets/06clubs.png", "assets/06diamond.png", "assets/06heart.png", "assets/06spade.png"],
+    ["assets/07clubs.png", "assets/07diamond.png", "assets/07heart.png", "assets/07spade.png"],
+    ["assets/08clubs.png", "assets/08diamond.png", "assets/08heart.png", "assets/08spade.png"],
+    ["assets/09clubs.png", "assets/09diamond.png", "assets/09heart.png", "assets/09spade.png"],
+    ["assets/10clubs.png", "assets/10diamond.png", "assets/10heart.png", "assets/10spade.png"],
+    ["assets/jackClubs.png", "assets/jackDiamond.png", "assets/jackHeart.png", "assets/jackSpade.png"],
+    ["assets/queenClubs.png", "assets/queenDiamond.png", "assets/queenHeart.png", "assets/queenSpade.png"],
+    ["assets/kingClubs.png", "assets/kingDiamond.png", "assets/kingHeart.png", "assets/kingSpade.png"],
+];
+
+impl Card {
+    pub fn new(rank: Rank, suit: Suit) -> Self {
+        Self { rank, suit }
+    }
+
+    /// Path of the card image, matches the keys used by TextureManager::get_preload
+    pub fn asset_path(&self) -> &'static str {
+        ASSET_PATHS[self.rank.index()][self.suit.index()]
+    }
+
+    /// One standard 52 card deck in a fixed order
+    pub fn full_deck() -> Vec<Card> {
+        let mut deck = Vec::with_capacity(52);
+        for suit in Suit::ALL {
+            for rank in Rank::ALL {
+                deck.push(Card::new(rank, suit));
+            }
+        }
+        deck
+    }
+
+    /// Every card image plus the card back, ready to hand to the TextureManager
+    pub fn all_asset_paths() -> Vec<&'static str> {
+        let mut paths: Vec<&'static str> = ASSET_PATHS.iter().flatten().copied().collect();
+        paths.push(CARD_BACK);
+        paths
+    }
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.rank, self.suit)
+    }
+}
+
+impl FromStr for Card {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        // The suit is always the last character, everything before it is the rank
+        let split = match s.char_indices().next_back() {
+            Some((idx, _)) if idx > 0 => idx,
+            _ => return Err(ParseCardError::TooShort(s.to_string())),
+        };
+        let rank = s[..split].parse::<Rank>()?;
+        let suit = s[split..].parse::<Suit>()?;
+        Ok(Card::new(rank, suit))
+    }
+}
+
+/// Errors from parsing a card written like "AH" or "10s"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseCardError {
+    TooShort(String),
+    BadRank(String),
+    BadSuit(String),
+}
+
+impl fmt::Display for ParseCardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseCardError::TooShort(s) => write!(f, "\"{}\" is too short to be a card", s),
+            ParseCardError::BadRank(s) => write!(f, "\"{}\" is not a card rank", s),
+            ParseCardError::BadSuit(s) => write!(f, "\"{}\" is not a card suit", s),
+        }
+    }
+}
+
+impl std::error::Error for ParseCardError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::path::Path;
+
+    #[test]
+    fn every_card_reads_back_what_it_writes() {
+        for card in Card::full_deck() {
+            assert_eq!(card.to_string().parse::<Card>(), Ok(card));
+            assert_eq!(card.to_string().to_lowercase().parse::<Card>(), Ok(card));
+        }
+        assert_eq!("10S".parse::<Card>(), Ok(Card::new(Rank::Ten, Suit::Spades)));
+        assert_eq!("ts".parse::<Card>(), Ok(Card::new(Rank::Ten, Suit::Spades)));
+        assert_eq!(" qd ".parse::<Card>(), Ok(Card::new(Rank::Queen, Suit::Diamonds)));
+        assert_eq!(Card::new(Rank::Ace, Suit::Hearts).to_string(), "AH");
+    }
+
+    #[test]
+    fn malformed_cards_are_refused() {
+        assert_eq!("".parse::<Card>(), Err(ParseCardError::TooShort(String::new())));
+        assert_eq!("H".parse::<Card>(), Err(ParseCardError::TooShort("H".to_string())));
+        assert_eq!("1x".parse::<Card>(), Err(ParseCardError::BadSuit("x".to_string())));
+        assert_eq!("11H".parse::<Card>(), Err(ParseCardError::BadRank("11".to_string())));
+        assert_eq!("0S".parse::<Card>(), Err(ParseCardError::BadRank("0".to_string())));
+        assert_eq!("ZC".parse::<Card>(), Err(ParseCardError::BadRank("Z".to_string())));
+    }
+
+    #[test]
+    fn every_rank_and_suit_has_its_own_image() {
+        let deck = Card::full_deck();
+        assert_eq!(deck.len(), 52);
+        let paths: HashSet<&str> = deck.iter().map(|card| card.asset_path()).collect();
+        assert_eq!(paths.len(), 52);
+        assert_eq!(Card::new(Rank::Ace, Suit::Hearts).asset_path(), "assets/aceHeart.png");
+        assert_eq!(Card::new(Rank::Ten, Suit::Clubs).asset_path(), "assets/10clubs.png");
+        assert_eq!(Card::new(Rank::King, Suit::Spades).asset_path(), "assets/kingSpade.png");
+        for path in Card::all_asset_paths() {
+            assert!(Path::new(env!("CARGO_MANIFEST_DIR")).join(path).is_file(), "{} is missing", path);
+        }
+    }
+
+    #[test]
+    fn face_cards_are_worth_ten_and_aces_one() {
+        let values: Vec<u8> = Rank::ALL.iter().map(|rank| rank.value()).collect();
+        assert_eq!(values, [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 10, 10, 10]);
+        assert!(Rank::Ace.is_ace());
+        assert!(Suit::Hearts.is_red() && Suit::Diamonds.is_red());
+        assert!(!Suit::Clubs.is_red() && !Suit::Spades.is_red());
+    }
+}
@@ -0,0 +1,295 @@
+/*
+Program Details: Card counting systems and a counter that follows the game
+
+In your game/mod.rs file add the following:
+    pub mod counting;
+
+Then with the other use commands add:
+use blackjack3::game::counting::{CardCounter, HiLo};
+
+Usage examples:
+    let mut counter = CardCounter::new(Box::new(HiLo), rules.decks);
+
+    // Feed it the events from every game action, it only counts cards the player can see
+    let events = game.hit()?;
+    counter.observe(&events);
+
+    counter.running_count();
+    counter.true_count(game.shoe().decks_remaining());
+
+    // Pick a system by name, for menus and saved settings
+    let system = system_by_name("Omega II").unwrap();
+
+    // Keep score of count quizzes
+    let mut score = QuizScore::default();
+    score.record(answer == counter.running_count());
+    score.accuracy();   // 0.0 to 1.0
+
+Systems:
+    Hi-Lo     2-6 +1, 7-9 0, 10-A -1                        balanced
+    KO        2-7 +1, 8-9 0, 10-A -1                        unbalanced, starts at 4 - 4 x decks
+    Omega II  2,3,7 +1, 4-6 +2, 8 0, 9 -1, 10-K -2, A 0     balanced
+
+The dealer's face down card is counted when it is turned over, and the count
+goes back to the start whenever the shoe is shuffled.
+*/
+use crate::game::card::{Card, Rank};
+use crate::game::engine::GameEvent;
+
+/// A way of giving each card a value to keep a running count
+pub trait CountingSystem {
+    fn name(&self) -> &'static str;
+
+    /// How much seeing this card changes the running count
+    fn tag(&self, rank: Rank) -> i32;
+
+    /// Balanced systems add up to 0 over a whole deck and use a true count
+    fn is_balanced(&self) -> bool {
+        true
+    }
+
+    /// Running count at the start of a shoe
+    fn initial_count(&self, _decks: usize) -> i32 {
+        0
+    }
+}
+
+pub struct HiLo;
+
+impl CountingSystem for HiLo {
+    fn name(&self) -> &'static str {
+        "Hi-Lo"
+    }
+
+    fn tag(&self, rank: Rank) -> i32 {
+        match rank.value() {
+            2..=6 => 1,
+            7..=9 => 0,
+            _ => -1,
+        }
+    }
+}
+
+/// Knock-Out, an unbalanced count that is used without a true count conversion
+pub struct Ko;
+
+impl CountingSystem for Ko {
+    fn name(&self) -> &'static str {
+        "KO"
+    }
+
+    fn tag(&self, rank: Rank) -> i32 {
+        match rank.value() {
+            2..=7 => 1,
+            8 | 9 => 0,
+            _ => -1,
+        }
+    }
+
+    fn is_balanced(&self) -> bool {
+        false
+    }
+
+    // Starting below zero makes the count reach the same key number whatever the number of decks
+    fn initial_count(&self, decks: usize) -> i32 {
+        4 - 4 * decks as i32
+    }
+}
+
+pub struct OmegaII;
+
+impl CountingSystem for OmegaII {
+    fn name(&self) -> &'static str {
+        "Omega II"
+    }
+
+    fn tag(&self, rank: Rank) -> i32 {
+        match rank.value() {
+            2 | 3 | 7 => 1,
+            4..=6 => 2,
+            8 => 0,
+            9 => -1,
+            10 => -2,
+            // Aces are 0, counted on the side by players who want to
+            _ => 0,
+        }
+    }
+}
+
+/// Names of every system, in the order a menu should show them
+pub const SYSTEM_NAMES: [&str; 3] = ["Hi-Lo", "KO", "Omega II"];
+
+pub fn system_by_name(name: &str) -> Option<Box<dyn CountingSystem>> {
+    match name {
+        "Hi-Lo" => Some(Box::new(HiLo)),
+        "KO" => Some(Box::new(Ko)),
+        "Omega II" => Some(Box::new(OmegaII)),
+        _ => None,
+    }
+}
+
+/// Keeps the count for one shoe with a counting system
+pub struct CardCounter {
+    system: Box<dyn CountingSystem>,
+    decks: usize,
+    running: i32,
+    seen: Vec<Rank>, // Every card counted since the shuffle, so the system can be changed mid shoe
+}
+
+impl CardCounter {
+    pub fn new(system: Box<dyn CountingSystem>, decks: usize) -> Self {
+        let running = system.initial_count(decks);
+        Self {
+            system,
+            decks,
+            running,
+            seen: Vec::new(),
+        }
+    }
+
+    pub fn system(&self) -> &dyn CountingSystem {
+        self.system.as_ref()
+    }
+
+    /// Changes the counting system and recounts the cards already seen this shoe with it
+    pub fn set_system(&mut self, system: Box<dyn CountingSystem>) {
+        self.system = system;
+        self.running = self.system.initial_count(self.decks);
+        for rank in &self.seen {
+            self.running += self.system.tag(*rank);
+        }
+    }
+
+    /// Back to the start of a fresh shoe
+    pub fn reset(&mut self) {
+        self.running = self.system.initial_count(self.decks);
+        self.seen.clear();
+    }
+
+    pub fn see(&mut self, card: Card) {
+        self.running += self.system.tag(card.rank);
+        self.seen.push(card.rank);
+    }
+
+    /// Counts the cards the player got to see in these events and starts over on a shuffle
+    pub fn observe(&mut self, events: &[GameEvent]) {
+        for event in events {
+            match event {
+                GameEvent::CardDealt { card, .. } | GameEvent::HoleCardRevealed(card) => self.see(*card),
+                GameEvent::Shuffled => self.reset(),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn running_count(&self) -> i32 {
+        self.running
+    }
+
+    /// Running count per deck left in the shoe. Unbalanced systems are played off the running count, so that is returned instead.
+    pub fn true_count(&self, decks_remaining: f32) -> f32 {
+        if !self.system.is_balanced() {
+            return self.running as f32;
+        }
+        // Less than half a deck left is treated as half a deck so the count does not run away
+        self.running as f32 / decks_remaining.max(0.5)
+    }
+
+    pub fn cards_seen(&self) -> usize {
+        self.seen.len()
+    }
+}
+
+/// How many count quizzes were answered and how many of them were right
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QuizScore {
+    pub asked: u32,
+    pub correct: u32,
+}
+
+impl QuizScore {
+    pub fn record(&mut self, correct: bool) {
+        self.asked += 1;
+        if correct {
+            self.correct += 1;
+        }
+    }
+
+    /// Fraction answered right, 0 before the first quiz
+    pub fn accuracy(&self) -> f32 {
+        if self.asked == 0 {
+            0.0
+        } else {
+            self.correct as f32 / self.asked as f32
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::engine::DealtTo;
+
+    fn tags(system: &dyn CountingSystem) -> Vec<i32> {
+        Rank::ALL.iter().map(|rank| system.tag(*rank)).collect()
+    }
+
+    fn dealt(text: &str) -> GameEvent {
+        GameEvent::CardDealt {
+            to: DealtTo::Dealer,
+            card: text.parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn hi_lo_tags() {
+        assert_eq!(tags(&HiLo), [-1, 1, 1, 1, 1, 1, 0, 0, 0, -1, -1, -1, -1]);
+        assert_eq!(tags(&HiLo).iter().sum::<i32>(), 0);
+    }
+
+    #[test]
+    fn ko_is_unbalanced_and_starts_below_zero() {
+        assert_eq!(tags(&Ko), [-1, 1, 1, 1, 1, 1, 1, 0, 0, -1, -1, -1, -1]);
+        // One deck adds up to +4, which the starting count takes away again
+        assert_eq!(tags(&Ko).iter().sum::<i32>() * 4, 4);
+        assert!(!Ko.is_balanced());
+        assert_eq!(Ko.initial_count(1), 0);
+        assert_eq!(Ko.initial_count(6), -20);
+        assert_eq!(CardCounter::new(Box::new(Ko), 6).running_count(), -20);
+    }
+
+    #[test]
+    fn omega_ii_tags() {
+        assert_eq!(tags(&OmegaII), [0, 1, 1, 2, 2, 2, 1, 0, -1, -2, -2, -2, -2]);
+        assert_eq!(tags(&OmegaII).iter().sum::<i32>(), 0);
+    }
+
+    #[test]
+    fn true_count_divides_by_the_decks_left() {
+        let mut counter = CardCounter::new(Box::new(HiLo), 6);
+        counter.observe(&[dealt("2H"), dealt("5C"), dealt("6D"), dealt("4S"), dealt("3H"), dealt("KS")]);
+        assert_eq!(counter.running_count(), 4);
+        assert_eq!(counter.true_count(2.0), 2.0);
+        // Under half a deck counts as half a deck
+        assert_eq!(counter.true_count(0.25), 8.0);
+
+        // KO has no true count, the running count is used as it is
+        let mut counter = CardCounter::new(Box::new(Ko), 1);
+        counter.observe(&[dealt("7H"), dealt("7C")]);
+        assert_eq!(counter.true_count(0.5), 2.0);
+    }
+
+    #[test]
+    fn the_hole_card_counts_when_it_is_turned_over_and_a_shuffle_starts_over() {
+        let mut counter = CardCounter::new(Box::new(HiLo), 6);
+        counter.observe(&[dealt("5H"), GameEvent::HoleCardDealt]);
+        assert_eq!(counter.running_count(), 1);
+        counter.observe(&[GameEvent::HoleCardRevealed("6C".parse().unwrap())]);
+        assert_eq!(counter.running_count(), 2);
+
+        // A shuffle in the middle of a deal resets the count before the next card
+        counter.observe(&[dealt("2C"), GameEvent::Shuffled, dealt("AS")]);
+        assert_eq!(counter.running_count(), -1);
+        assert_eq!(counter.cards_seen(), 1);
+    }
+}
@@ -0,0 +1,1508 @@
+/*
+Program Details: Headless blackjack game engine
+
+The engine holds everything about a round (shoe, seats, bankrolls, bets, hands)
+and moves through the phases of the game. It never draws anything, it only
+returns the events that happened so the GUI (or anything else) can show them.
+
+In your game/mod.rs file add the following:
+    pub mod engine;
+
+Then with the other use commands add:
+use blackjack3::game::engine::{BlackjackGame, GameEvent, Phase};
+
+Usage examples:
+    let mut game = BlackjackGame::new(TableRules::default(), 500, seed);
+
+    game.place_bet(25)?;        // seat 0 bets, the table stays in Betting
+    game.deal()?;               // Betting -> Dealing -> Insurance when the dealer shows an ace, otherwise PlayerTurn
+    game.decline_insurance()?;  // or take_insurance(amount) / take_even_money(), asked seat by seat
+    game.decline_surrender()?;  // or surrender(), asked seat by seat with a ten showing at an early surrender table
+    game.hit()?;                // hit, stand, double, split and surrender play the active hand
+    game.stand()?;              // after the last hand of the last seat -> DealerTurn
+    game.play_dealer()?;        // DealerTurn -> Settlement
+    game.next_round()?;         // Settlement -> Betting
+
+    // More seats, up to MAX_SEATS. Seats without a bet sit the round out
+    let seat = game.add_seat(500)?;
+    game.place_bet_at(seat, 10)?;
+    game.active_seat();         // whose turn it is before the peek and during PlayerTurn
+    game.seats()[seat].hands(); // each seat is settled on its own
+
+Every action returns Ok(Vec<GameEvent>) describing what happened, or a
+GameError if the action is not allowed right now. A failed action never
+changes the game. The play actions always act for the active seat.
+
+bankroll(), hands(), insurance(), total_staked(), total_payout(), set_bankroll()
+and place_bet() are shortcuts for seat 0, the only seat at a one seat table.
+
+The dealer gets an up card and a face down hole card. With an ace or a ten
+showing the dealer peeks at the hole card, and a dealer blackjack ends the
+round straight away. dealer() is the whole hand, dealer_showing() is what
+the player is allowed to see. Early surrender comes before the peek: during
+Insurance with an ace up, and in Dealing with a ten up.
+
+Side bets (see the side_bets module) go down with the main bet and are paid
+from the first two cards and the dealer's up card, before anybody plays.
+
+Each round is recorded from the deal to the settlement (seed, shoe position,
+cards and events), see round_record() and the replay module.
+*/
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::card::Card;
+use crate::game::hand::Hand;
+use crate::game::limits::BetError;
+use crate::game::replay::{Action, RoundRecord};
+use crate::game::rules::{Surrender, TableRules};
+use crate::game::shoe::Shoe;
+use crate::game::side_bets::{resolve, SideBet, SideBetResult, SideBetStake};
+
+/// Most seats a table can have
+pub const MAX_SEATS: usize = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Phase {
+    Betting,
+    Dealing,
+    Insurance,
+    PlayerTurn,
+    DealerTurn,
+    Settlement,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Outcome {
+    Blackjack,
+    Win,
+    Push,
+    Lose,
+    Bust,
+    Surrender,
+}
+
+/// Who a card was dealt to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DealtTo {
+    Player { seat: usize, hand: usize },
+    Dealer,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameEvent {
+    PhaseChanged(Phase),
+    BetPlaced {
+        seat: usize,
+        amount: u32,
+    },
+    CardDealt {
+        to: DealtTo,
+        card: Card,
+    },
+    HoleCardDealt,
+    HoleCardRevealed(Card),
+    SideBetPlaced {
+        seat: usize,
+        bet: SideBet,
+        amount: u32,
+    },
+    /// A side bet worked out after the deal, result None when it lost
+    SideBetSettled {
+        seat: usize,
+        bet: SideBet,
+        result: Option<SideBetResult>,
+        payout: u32,
+    },
+    InsuranceTaken {
+        seat: usize,
+        amount: u32,
+    },
+    EvenMoneyTaken {
+        seat: usize,
+    },
+    InsuranceSettled {
+        seat: usize,
+        payout: u32,
+    },
+    DealerBlackjack,
+    ActiveHand {
+        seat: usize,
+        hand: usize,
+    },
+    Doubled {
+        seat: usize,
+        hand: usize,
+    },
+    Split {
+        seat: usize,
+        hand: usize,
+        new_hand: usize,
+    },
+    Surrendered {
+        seat: usize,
+        hand: usize,
+    },
+    Busted {
+        seat: usize,
+        hand: usize,
+    },
+    DealerStood {
+        total: u8,
+    },
+    DealerBusted,
+    Settled {
+        seat: usize,
+        hand: usize,
+        outcome: Outcome,
+        payout: u32,
+    },
+    Shuffled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameError {
+    WrongPhase(Phase),
+    /// A bet outside the table limits or the seat's chips
+    Bet(BetError),
+    NoBets,
+    NoSuchSeat,
+    TableFull,
+    CannotHit,
+    CannotDouble,
+    CannotSplit,
+    CannotSurrender,
+    InvalidInsurance,
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::WrongPhase(phase) => write!(f, "Not allowed during {:?}", phase),
+            GameError::Bet(err) => write!(f, "{}", err),
+            GameError::NoBets => write!(f, "Nobody has bet"),
+            GameError::NoSuchSeat => write!(f, "No such seat"),
+            GameError::TableFull => write!(f, "The table is full"),
+            GameError::CannotHit => write!(f, "Cannot hit this hand"),
+            GameError::CannotDouble => write!(f, "Cannot double this hand"),
+            GameError::CannotSplit => write!(f, "Cannot split this hand"),
+            GameError::CannotSurrender => write!(f, "Cannot surrender this hand"),
+            GameError::InvalidInsurance => write!(f, "Insurance is up to half the bet"),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+impl From<BetError> for GameError {
+    fn from(err: BetError) -> Self {
+        GameError::Bet(err)
+    }
+}
+
+/// One of a seat's hands along with the chips riding on it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerHand {
+    pub hand: Hand,
+    pub bet: u32,
+    pub doubled: bool,
+    pub surrendered: bool,
+    pub from_split: bool,
+    pub split_aces: bool,
+    pub even_money: bool,
+    pub finished: bool,
+    pub outcome: Option<Outcome>,
+    pub payout: u32,
+}
+
+impl PlayerHand {
+    fn new(bet: u32) -> Self {
+        Self {
+            hand: Hand::new(),
+            bet,
+            doubled: false,
+            surrendered: false,
+            from_split: false,
+            split_aces: false,
+            even_money: false,
+            finished: false,
+            outcome: None,
+            payout: 0,
+        }
+    }
+
+    // Still in the running against the dealer
+    fn is_live(&self) -> bool {
+        !self.surrendered && !self.hand.is_bust()
+    }
+
+    /// Blackjack on the first two cards, 21 on a split hand does not count
+    pub fn is_natural(&self) -> bool {
+        self.hand.is_blackjack() && !self.from_split
+    }
+}
+
+/// A place at the table with its own bankroll, bet and hands
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Seat {
+    bankroll: u32,
+    bet: u32,
+    hands: Vec<PlayerHand>,
+    insurance: u32,
+    insurance_payout: u32,
+    side_bets: Vec<SideBetStake>,
+}
+
+impl Seat {
+    fn new(bankroll: u32) -> Self {
+        Self {
+            bankroll,
+            bet: 0,
+            hands: Vec::new(),
+            insurance: 0,
+            insurance_payout: 0,
+            side_bets: Vec::new(),
+        }
+    }
+
+    pub fn bankroll(&self) -> u32 {
+        self.bankroll
+    }
+
+    /// Bet for the next round while betting, then the first bet of the round. 0 when sitting out.
+    pub fn bet(&self) -> u32 {
+        self.bet
+    }
+
+    /// Hands in the round, empty for a seat that is sitting out
+    pub fn hands(&self) -> &[PlayerHand] {
+        &self.hands
+    }
+
+    /// True when the seat has hands in this round
+    pub fn is_playing(&self) -> bool {
+        !self.hands.is_empty()
+    }
+
+    /// Insurance bet for this round, 0 when none was taken
+    pub fn insurance(&self) -> u32 {
+        self.insurance
+    }
+
+    /// Side bets for the next round while betting, then this round's with their results
+    pub fn side_bets(&self) -> &[SideBetStake] {
+        &self.side_bets
+    }
+
+    /// Chips on one side bet, 0 when it was not made
+    pub fn side_bet(&self, bet: SideBet) -> u32 {
+        self.side_bets.iter().find(|stake| stake.bet == bet).map_or(0, |stake| stake.amount)
+    }
+
+    /// Chips bet this round, insurance and side bets included
+    pub fn total_staked(&self) -> u32 {
+        self.hands.iter().map(|h| h.bet).sum::<u32>() + self.insurance + self.side_bets.iter().map(|stake| stake.amount).sum::<u32>()
+    }
+
+    /// Chips won back this round (stakes, insurance and side bets included), once the round is settled
+    pub fn total_payout(&self) -> u32 {
+        self.hands.iter().map(|h| h.payout).sum::<u32>() + self.insurance_payout + self.side_bets.iter().map(|stake| stake.payout).sum::<u32>()
+    }
+
+    // Chips put down for the next round, main bet and side bets
+    fn chips_down(&self) -> u32 {
+        self.bet + self.side_bets.iter().map(|stake| stake.amount).sum::<u32>()
+    }
+
+    fn clear_round(&mut self) {
+        self.bet = 0;
+        self.hands.clear();
+        self.insurance = 0;
+        self.insurance_payout = 0;
+        self.side_bets.clear();
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BlackjackGame {
+    rules: TableRules,
+    shoe: Shoe,
+    phase: Phase,
+    seats: Vec<Seat>,
+    dealer: Hand,
+    hole_card_hidden: bool,
+    active_seat: usize, // Seat deciding on insurance or playing
+    active: usize,      // Hand of the active seat being played
+    record: Option<RoundRecord>,
+}
+
+impl BlackjackGame {
+    /// Starts a one seat game at a table with these rules, the shoe is built from the rules and shuffled with the seed
+    pub fn new(rules: TableRules, bankroll: u32, seed: u64) -> Self {
+        let shoe = Shoe::new(rules.decks, rules.penetration, seed);
+        Self {
+            rules,
+            shoe,
+            phase: Phase::Betting,
+            seats: vec![Seat::new(bankroll)],
+            dealer: Hand::new(),
+            hole_card_hidden: false,
+            active_seat: 0,
+            active: 0,
+            record: None,
+        }
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    pub fn seats(&self) -> &[Seat] {
+        &self.seats
+    }
+
+    /// Sits a new player down on the right of the others, returns their seat
+    pub fn add_seat(&mut self, bankroll: u32) -> Result<usize, GameError> {
+        self.expect_phase(Phase::Betting)?;
+        if self.seats.len() >= MAX_SEATS {
+            return Err(GameError::TableFull);
+        }
+        self.seats.push(Seat::new(bankroll));
+        Ok(self.seats.len() - 1)
+    }
+
+    /// Takes a seat away (its bets go back first), the seats after it move down one.
+    /// The last seat cannot be removed.
+    pub fn remove_seat(&mut self, seat: usize) -> Result<u32, GameError> {
+        self.expect_phase(Phase::Betting)?;
+        if seat >= self.seats.len() || self.seats.len() == 1 {
+            return Err(GameError::NoSuchSeat);
+        }
+        let removed = self.seats.remove(seat);
+        Ok(removed.bankroll + removed.chips_down())
+    }
+
+    /// Replaces a seat's bankroll, for example when a broke player buys back in
+    pub fn set_bankroll_at(&mut self, seat: usize, bankroll: u32) -> Result<(), GameError> {
+        self.seats.get_mut(seat).ok_or(GameError::NoSuchSeat)?.bankroll = bankroll;
+        Ok(())
+    }
+
+    /// Seat 0's bankroll
+    pub fn bankroll(&self) -> u32 {
+        self.seats[0].bankroll
+    }
+
+    /// Replaces seat 0's bankroll
+    pub fn set_bankroll(&mut self, bankroll: u32) {
+        self.seats[0].bankroll = bankroll;
+    }
+
+    /// Seat 0's hands
+    pub fn hands(&self) -> &[PlayerHand] {
+        &self.seats[0].hands
+    }
+
+    /// Seat 0's insurance
+    pub fn insurance(&self) -> u32 {
+        self.seats[0].insurance
+    }
+
+    /// Chips seat 0 bet this round, insurance included
+    pub fn total_staked(&self) -> u32 {
+        self.seats[0].total_staked()
+    }
+
+    /// Chips won back by seat 0 this round
+    pub fn total_payout(&self) -> u32 {
+        self.seats[0].total_payout()
+    }
+
+    pub fn rules(&self) -> &TableRules {
+        &self.rules
+    }
+
+    pub fn shoe(&self) -> &Shoe {
+        &self.shoe
+    }
+
+    /// The dealer's whole hand, including the hole card while it is still face down
+    pub fn dealer(&self) -> &Hand {
+        &self.dealer
+    }
+
+    pub fn hole_card_hidden(&self) -> bool {
+        self.hole_card_hidden
+    }
+
+    /// The dealer's cards the player can see
+    pub fn dealer_showing(&self) -> Hand {
+        if self.hole_card_hidden {
+            Hand::from_cards(&self.dealer.cards()[..1])
+        } else {
+            self.dealer.clone()
+        }
+    }
+
+    /// Seat that has to decide next, before the peek and during PlayerTurn
+    pub fn active_seat(&self) -> Option<usize> {
+        match self.phase {
+            Phase::Dealing | Phase::Insurance | Phase::PlayerTurn => Some(self.active_seat),
+            _ => None,
+        }
+    }
+
+    /// Index of the active seat's hand being played, only during the player's turn
+    pub fn active_hand(&self) -> Option<usize> {
+        if self.phase == Phase::PlayerTurn {
+            Some(self.active)
+        } else {
+            None
+        }
+    }
+
+    /// The record of the round being played, or of the last round between rounds. None before the first deal.
+    pub fn round_record(&self) -> Option<&RoundRecord> {
+        self.record.as_ref()
+    }
+
+    fn expect_phase(&self, phase: Phase) -> Result<(), GameError> {
+        if self.phase == phase {
+            Ok(())
+        } else {
+            Err(GameError::WrongPhase(self.phase))
+        }
+    }
+
+    fn set_phase(&mut self, phase: Phase, events: &mut Vec<GameEvent>) {
+        self.phase = phase;
+        events.push(GameEvent::PhaseChanged(phase));
+    }
+
+    // Adds the events of an action to the round record before handing them back
+    fn logged(&mut self, events: Vec<GameEvent>) -> Result<Vec<GameEvent>, GameError> {
+        if let Some(record) = &mut self.record {
+            record.events.extend(events.iter().cloned());
+        }
+        Ok(events)
+    }
+
+    // Keeps the active seat's decisions in the round record
+    fn note(&mut self, action: Action) {
+        let seat = self.active_seat;
+        if let Some(record) = &mut self.record {
+            record.actions.push((seat, action));
+        }
+    }
+
+    // Every card of a round comes through here so the record has the shoe order.
+    // A shoe that runs out is shuffled on the spot, and the counters need to hear about that too
+    fn draw(&mut self, events: &mut Vec<GameEvent>) -> Card {
+        let shuffles = self.shoe.shuffle_number();
+        let card = self.shoe.deal();
+        if self.shoe.shuffle_number() != shuffles {
+            events.push(GameEvent::Shuffled);
+        }
+        if let Some(record) = &mut self.record {
+            record.cards.push(card);
+        }
+        card
+    }
+
+    fn deal_to_player(&mut self, seat: usize, hand: usize, events: &mut Vec<GameEvent>) {
+        let card = self.draw(events);
+        self.seats[seat].hands[hand].hand.push(card);
+        events.push(GameEvent::CardDealt {
+            to: DealtTo::Player { seat, hand },
+            card,
+        });
+    }
+
+    fn deal_to_dealer(&mut self, events: &mut Vec<GameEvent>) {
+        let card = self.draw(events);
+        self.dealer.push(card);
+        events.push(GameEvent::CardDealt { to: DealtTo::Dealer, card });
+    }
+
+    // The hand being played
+    fn current(&self) -> &PlayerHand {
+        &self.seats[self.active_seat].hands[self.active]
+    }
+
+    fn current_mut(&mut self) -> &mut PlayerHand {
+        &mut self.seats[self.active_seat].hands[self.active]
+    }
+
+    /// Puts a seat's bet down for the next round, replacing any bet it already made
+    pub fn place_bet_at(&mut self, seat: usize, amount: u32) -> Result<Vec<GameEvent>, GameError> {
+        self.expect_phase(Phase::Betting)?;
+        let player = self.seats.get_mut(seat).ok_or(GameError::NoSuchSeat)?;
+        self.rules.limits.check(amount, player.bankroll + player.bet)?;
+
+        player.bankroll = player.bankroll + player.bet - amount;
+        player.bet = amount;
+        Ok(vec![GameEvent::BetPlaced { seat, amount }])
+    }
+
+    /// Seat 0 bets
+    pub fn place_bet(&mut self, amount: u32) -> Result<Vec<GameEvent>, GameError> {
+        self.place_bet_at(0, amount)
+    }
+
+    /// Takes a seat's bets back, side bets too, so it sits the next round out
+    pub fn clear_bet(&mut self, seat: usize) -> Result<(), GameError> {
+        self.expect_phase(Phase::Betting)?;
+        let player = self.seats.get_mut(seat).ok_or(GameError::NoSuchSeat)?;
+        player.bankroll += player.chips_down();
+        player.bet = 0;
+        player.side_bets.clear();
+        Ok(())
+    }
+
+    /// Puts a side bet down for the next round, replacing the seat's earlier one.
+    /// Side bets keep to the table limits like the main bet. An amount of 0 takes the side bet back.
+    pub fn place_side_bet_at(&mut self, seat: usize, bet: SideBet, amount: u32) -> Result<Vec<GameEvent>, GameError> {
+        self.expect_phase(Phase::Betting)?;
+        let limits = self.rules.limits;
+        let player = self.seats.get_mut(seat).ok_or(GameError::NoSuchSeat)?;
+        let earlier = player.side_bet(bet);
+        if amount > 0 {
+            limits.check(amount, player.bankroll + earlier)?;
+        }
+
+        player.bankroll = player.bankroll + earlier - amount;
+        player.side_bets.retain(|stake| stake.bet != bet);
+        if amount == 0 {
+            return Ok(Vec::new());
+        }
+        player.side_bets.push(SideBetStake {
+            bet,
+            amount,
+            result: None,
+            payout: 0,
+        });
+        Ok(vec![GameEvent::SideBetPlaced { seat, bet, amount }])
+    }
+
+    // Seats with hands this round, in the order they play
+    fn playing_seats(&self) -> Vec<usize> {
+        (0..self.seats.len()).filter(|seat| self.seats[*seat].is_playing()).collect()
+    }
+
+    /// Deals two cards to every seat that bet and to the dealer, one at a time
+    /// round the table, the dealer's second card face down. The table is Dealing
+    /// until the cards are out, then an ace showing stops at Insurance and a ten
+    /// showing at an early surrender table stays in Dealing for the surrender
+    /// question. Otherwise the dealer peeks and play starts.
+    pub fn deal(&mut self) -> Result<Vec<GameEvent>, GameError> {
+        self.expect_phase(Phase::Betting)?;
+        if self.seats.iter().all(|seat| seat.bet == 0) {
+            return Err(GameError::NoBets);
+        }
+
+        let mut record = RoundRecord::new(&self.shoe, self.seats.iter().map(|seat| seat.bet).collect());
+        for seat in &mut self.seats {
+            seat.hands.clear();
+            seat.insurance = 0;
+            seat.insurance_payout = 0;
+            if seat.bet > 0 {
+                seat.hands.push(PlayerHand::new(seat.bet));
+            } else {
+                // Side bets need a main bet to ride along with
+                seat.bankroll += seat.chips_down();
+                seat.side_bets.clear();
+            }
+        }
+        for (seat, player) in self.seats.iter().enumerate() {
+            if player.bet > 0 {
+                record.events.push(GameEvent::BetPlaced { seat, amount: player.bet });
+            }
+        }
+        self.record = Some(record);
+        self.dealer.clear();
+        self.hole_card_hidden = false;
+        self.active = 0;
+
+        let mut events = Vec::new();
+        self.set_phase(Phase::Dealing, &mut events);
+        let playing = self.playing_seats();
+        for seat in &playing {
+            self.deal_to_player(*seat, 0, &mut events);
+        }
+        self.deal_to_dealer(&mut events);
+        for seat in &playing {
+            self.deal_to_player(*seat, 0, &mut events);
+        }
+        let hole = self.draw(&mut events);
+        self.dealer.push(hole);
+        self.hole_card_hidden = true;
+        events.push(GameEvent::HoleCardDealt);
+        self.settle_side_bets(&mut events);
+
+        let up = self.dealer.cards()[0].rank;
+        if up.is_ace() {
+            self.active_seat = playing[0];
+            self.set_phase(Phase::Insurance, &mut events);
+        } else if up.value() == 10 && self.rules.surrender == Surrender::Early {
+            self.active_seat = playing[0];
+        } else {
+            self.peek(&mut events);
+        }
+        self.logged(events)
+    }
+
+    // Pays the side bets from the first two cards and the up card, before the dealer peeks
+    fn settle_side_bets(&mut self, events: &mut Vec<GameEvent>) {
+        let up = self.dealer.cards()[0];
+        for (seat, player) in self.seats.iter_mut().enumerate() {
+            let Some(first) = player.hands.first() else {
+                continue;
+            };
+            let (one, two) = (first.hand.cards()[0], first.hand.cards()[1]);
+            for stake in &mut player.side_bets {
+                stake.result = resolve(stake.bet, one, two, up);
+                stake.payout = self.rules.side_bets.payout(stake.amount, stake.result);
+                player.bankroll = player.bankroll.saturating_add(stake.payout);
+                events.push(GameEvent::SideBetSettled {
+                    seat,
+                    bet: stake.bet,
+                    result: stake.result,
+                    payout: stake.payout,
+                });
+            }
+        }
+    }
+
+    /// Most insurance the active seat can take: half the bet, or whatever chips are left
+    pub fn max_insurance(&self) -> u32 {
+        if self.phase != Phase::Insurance {
+            return 0;
+        }
+        let seat = &self.seats[self.active_seat];
+        (seat.hands[0].bet / 2).min(seat.bankroll)
+    }
+
+    /// True when the active seat holds a natural against the dealer's ace and can take even money
+    pub fn can_take_even_money(&self) -> bool {
+        self.phase == Phase::Insurance && self.seats[self.active_seat].hands[0].is_natural() && !self.seats[self.active_seat].hands[0].finished
+    }
+
+    /// Side bet of up to half the main bet that pays 2:1 if the dealer has blackjack
+    pub fn take_insurance(&mut self, amount: u32) -> Result<Vec<GameEvent>, GameError> {
+        self.expect_phase(Phase::Insurance)?;
+        if amount == 0 || amount > self.max_insurance() {
+            return Err(GameError::InvalidInsurance);
+        }
+
+        let mut events = Vec::new();
+        self.note(Action::Insurance { amount });
+        let seat = self.active_seat;
+        self.seats[seat].bankroll -= amount;
+        self.seats[seat].insurance = amount;
+        events.push(GameEvent::InsuranceTaken { seat, amount });
+        self.next_seat_before_peek(&mut events);
+        self.logged(events)
+    }
+
+    /// Takes a guaranteed 1:1 win on a natural instead of risking a push with the dealer
+    pub fn take_even_money(&mut self) -> Result<Vec<GameEvent>, GameError> {
+        self.expect_phase(Phase::Insurance)?;
+        if !self.can_take_even_money() {
+            return Err(GameError::InvalidInsurance);
+        }
+
+        let mut events = Vec::new();
+        self.note(Action::EvenMoney);
+        let seat = self.active_seat;
+        self.seats[seat].hands[0].even_money = true;
+        events.push(GameEvent::EvenMoneyTaken { seat });
+        self.next_seat_before_peek(&mut events);
+        self.logged(events)
+    }
+
+    pub fn decline_insurance(&mut self) -> Result<Vec<GameEvent>, GameError> {
+        self.expect_phase(Phase::Insurance)?;
+
+        let mut events = Vec::new();
+        self.note(Action::DeclineInsurance);
+        self.next_seat_before_peek(&mut events);
+        self.logged(events)
+    }
+
+    /// Keeps the hand against a ten, the answer to the early surrender question before the peek
+    pub fn decline_surrender(&mut self) -> Result<Vec<GameEvent>, GameError> {
+        self.expect_phase(Phase::Dealing)?;
+
+        let mut events = Vec::new();
+        self.note(Action::DeclineSurrender);
+        self.next_seat_before_peek(&mut events);
+        self.logged(events)
+    }
+
+    // Asks the next seat about insurance or early surrender, the dealer peeks once every seat has answered
+    fn next_seat_before_peek(&mut self, events: &mut Vec<GameEvent>) {
+        match self.playing_seats().into_iter().find(|seat| *seat > self.active_seat) {
+            Some(seat) => self.active_seat = seat,
+            None => self.peek(events),
+        }
+    }
+
+    fn reveal_hole_card(&mut self, events: &mut Vec<GameEvent>) {
+        if self.hole_card_hidden {
+            self.hole_card_hidden = false;
+            events.push(GameEvent::HoleCardRevealed(self.dealer.cards()[1]));
+        }
+    }
+
+    // Dealer checks for blackjack with an ace or ten up. Insurance is paid or lost here,
+    // then the round either ends or the first seat starts playing
+    fn peek(&mut self, events: &mut Vec<GameEvent>) {
+        let up = self.dealer.cards()[0].rank;
+        let dealer_blackjack = (up.is_ace() || up.value() == 10) && self.dealer.is_blackjack();
+
+        for seat in 0..self.seats.len() {
+            let player = &mut self.seats[seat];
+            if player.insurance > 0 {
+                player.insurance_payout = if dealer_blackjack { player.insurance * 3 } else { 0 };
+                player.bankroll += player.insurance_payout;
+                events.push(GameEvent::InsuranceSettled {
+                    seat,
+                    payout: player.insurance_payout,
+                });
+            }
+        }
+
+        if dealer_blackjack {
+            self.reveal_hole_card(events);
+            events.push(GameEvent::DealerBlackjack);
+            self.settle(events);
+            return;
+        }
+
+        self.set_phase(Phase::PlayerTurn, events);
+        self.advance(events);
+    }
+
+    /// Split aces only get one card each, so they cannot be hit
+    pub fn can_hit(&self) -> bool {
+        self.phase == Phase::PlayerTurn && !self.current().split_aces
+    }
+
+    pub fn can_double(&self) -> bool {
+        self.phase == Phase::PlayerTurn && {
+            let current = self.current();
+            current.hand.len() == 2
+                && !current.split_aces
+                && current.bet <= self.seats[self.active_seat].bankroll
+                && (!current.from_split || self.rules.double_after_split)
+        }
+    }
+
+    pub fn can_split(&self) -> bool {
+        self.phase == Phase::PlayerTurn && {
+            let seat = &self.seats[self.active_seat];
+            let current = self.current();
+            let cards = current.hand.cards();
+            let resplitting_aces = current.from_split && cards.first().is_some_and(|card| card.rank.is_ace());
+            cards.len() == 2
+                && cards[0].rank == cards[1].rank
+                && seat.hands.len() < self.rules.max_hands
+                && current.bet <= seat.bankroll
+                && (!resplitting_aces || self.rules.resplit_aces)
+        }
+    }
+
+    /// Surrender is on the first two cards of an unsplit hand. Early surrender can
+    /// also be taken before the dealer peeks, against an ace or a ten.
+    pub fn can_surrender(&self) -> bool {
+        let timing = match self.rules.surrender {
+            Surrender::None => false,
+            Surrender::Late => self.phase == Phase::PlayerTurn,
+            Surrender::Early => matches!(self.phase, Phase::Dealing | Phase::Insurance | Phase::PlayerTurn),
+        };
+        timing && {
+            let seat = &self.seats[self.active_seat];
+            seat.hands.len() == 1 && seat.hands[0].hand.len() == 2 && !seat.hands[0].finished
+        }
+    }
+
+    pub fn hit(&mut self) -> Result<Vec<GameEvent>, GameError> {
+        self.expect_phase(Phase::PlayerTurn)?;
+        if !self.can_hit() {
+            return Err(GameError::CannotHit);
+        }
+
+        let mut events = Vec::new();
+        self.note(Action::Hit);
+        let (seat, hand) = (self.active_seat, self.active);
+        self.deal_to_player(seat, hand, &mut events);
+        if self.current().hand.is_bust() {
+            events.push(GameEvent::Busted { seat, hand });
+            self.finish_active_hand(&mut events);
+        } else if self.current().hand.best_total() == 21 {
+            self.finish_active_hand(&mut events);
+        }
+        self.logged(events)
+    }
+
+    pub fn stand(&mut self) -> Result<Vec<GameEvent>, GameError> {
+        self.expect_phase(Phase::PlayerTurn)?;
+
+        let mut events = Vec::new();
+        self.note(Action::Stand);
+        self.finish_active_hand(&mut events);
+        self.logged(events)
+    }
+
+    /// Doubles the bet, takes exactly one more card and stands
+    pub fn double(&mut self) -> Result<Vec<GameEvent>, GameError> {
+        self.expect_phase(Phase::PlayerTurn)?;
+        if !self.can_double() {
+            return Err(GameError::CannotDouble);
+        }
+
+        let mut events = Vec::new();
+        self.note(Action::Double);
+        let (seat, hand) = (self.active_seat, self.active);
+        let bet = self.current().bet;
+        self.seats[seat].bankroll -= bet;
+        self.current_mut().bet *= 2;
+        self.current_mut().doubled = true;
+        events.push(GameEvent::Doubled { seat, hand });
+        self.deal_to_player(seat, hand, &mut events);
+        if self.current().hand.is_bust() {
+            events.push(GameEvent::Busted { seat, hand });
+        }
+        self.finish_active_hand(&mut events);
+        self.logged(events)
+    }
+
+    /// Splits a pair into two hands with the same bet, the new hand is played next.
+    /// Split aces get one card each and stand, unless they can be split again.
+    pub fn split(&mut self) -> Result<Vec<GameEvent>, GameError> {
+        self.expect_phase(Phase::PlayerTurn)?;
+        if !self.can_split() {
+            return Err(GameError::CannotSplit);
+        }
+
+        let mut events = Vec::new();
+        self.note(Action::Split);
+        let (seat, hand) = (self.active_seat, self.active);
+        let new_hand = hand + 1;
+        let bet = self.current().bet;
+        self.seats[seat].bankroll -= bet;
+
+        let cards = self.current().hand.cards().to_vec();
+        let aces = cards[0].rank.is_ace();
+        let mut first = PlayerHand::new(bet);
+        first.from_split = true;
+        first.split_aces = aces;
+        first.hand.push(cards[0]);
+        let mut second = PlayerHand::new(bet);
+        second.from_split = true;
+        second.split_aces = aces;
+        second.hand.push(cards[1]);
+        self.seats[seat].hands[hand] = first;
+        self.seats[seat].hands.insert(new_hand, second);
+        events.push(GameEvent::Split { seat, hand, new_hand });
+
+        // The current hand gets its second card now, the new one when it becomes active
+        self.deal_to_player(seat, hand, &mut events);
+        if self.active_hand_is_done() {
+            self.finish_active_hand(&mut events);
+        }
+        self.logged(events)
+    }
+
+    /// Gives up the hand and gets half the bet back
+    pub fn surrender(&mut self) -> Result<Vec<GameEvent>, GameError> {
+        if !matches!(self.phase, Phase::Dealing | Phase::Insurance) {
+            self.expect_phase(Phase::PlayerTurn)?;
+        }
+        if !self.can_surrender() {
+            return Err(GameError::CannotSurrender);
+        }
+
+        let mut events = Vec::new();
+        self.note(Action::Surrender);
+        let seat = self.active_seat;
+        self.seats[seat].hands[0].surrendered = true;
+        events.push(GameEvent::Surrendered { seat, hand: 0 });
+        if self.phase != Phase::PlayerTurn {
+            // Early surrender before the peek answers the insurance question too
+            self.seats[seat].hands[0].finished = true;
+            self.next_seat_before_peek(&mut events);
+        } else {
+            self.finish_active_hand(&mut events);
+        }
+        self.logged(events)
+    }
+
+    // True when the active hand has nothing left to decide: 21, or split aces that cannot be split again
+    fn active_hand_is_done(&self) -> bool {
+        let current = self.current();
+        current.hand.best_total() == 21 || (current.split_aces && !self.can_split())
+    }
+
+    // Marks the active hand as done and moves on
+    fn finish_active_hand(&mut self, events: &mut Vec<GameEvent>) {
+        self.current_mut().finished = true;
+        self.advance(events);
+    }
+
+    // Moves on to the next hand still to be played, seat by seat, or to the dealer when there is none
+    fn advance(&mut self, events: &mut Vec<GameEvent>) {
+        loop {
+            let next = self
+                .seats
+                .iter()
+                .enumerate()
+                .find_map(|(seat, player)| player.hands.iter().position(|h| !h.finished).map(|hand| (seat, hand)));
+            let Some((seat, hand)) = next else {
+                break;
+            };
+            self.active_seat = seat;
+            self.active = hand;
+            events.push(GameEvent::ActiveHand { seat, hand });
+            // Split hands only have one card until they are played
+            if self.current().hand.len() == 1 {
+                self.deal_to_player(seat, hand, events);
+            }
+            if self.active_hand_is_done() {
+                self.current_mut().finished = true;
+            } else {
+                return;
+            }
+        }
+
+        self.set_phase(Phase::DealerTurn, events);
+    }
+
+    // Every hand at the table, seat by seat
+    fn all_hands(&self) -> impl Iterator<Item = &PlayerHand> {
+        self.seats.iter().flat_map(|seat| seat.hands.iter())
+    }
+
+    /// Plays one step of the dealer's turn: draws a card if the dealer needs one,
+    /// otherwise settles the round
+    pub fn dealer_step(&mut self) -> Result<Vec<GameEvent>, GameError> {
+        self.expect_phase(Phase::DealerTurn)?;
+
+        let mut events = Vec::new();
+        if self.hole_card_hidden {
+            self.reveal_hole_card(&mut events);
+            return self.logged(events);
+        }
+
+        let anyone_left = self.all_hands().any(|h| h.is_live());
+        // The dealer only draws if some hand still needs beating, a natural already won at the peek
+        let must_play = self.all_hands().any(|h| h.is_live() && !h.is_natural());
+        if must_play && self.dealer_should_hit() {
+            self.deal_to_dealer(&mut events);
+            return self.logged(events);
+        }
+
+        if self.dealer.is_bust() {
+            events.push(GameEvent::DealerBusted);
+        } else if anyone_left {
+            events.push(GameEvent::DealerStood {
+                total: self.dealer.best_total(),
+            });
+        }
+        self.settle(&mut events);
+        self.logged(events)
+    }
+
+    // Dealer hits anything under 17, and soft 17 too when the table is H17
+    fn dealer_should_hit(&self) -> bool {
+        let total = self.dealer.best_total();
+        total < 17 || (total == 17 && self.dealer.is_soft() && self.rules.dealer_hits_soft_17)
+    }
+
+    /// Runs the whole dealer turn and settles the round
+    pub fn play_dealer(&mut self) -> Result<Vec<GameEvent>, GameError> {
+        self.expect_phase(Phase::DealerTurn)?;
+
+        let mut events = Vec::new();
+        while self.phase == Phase::DealerTurn {
+            events.extend(self.dealer_step()?);
+        }
+        Ok(events)
+    }
+
+    // Every seat is settled against the dealer on its own
+    fn settle(&mut self, events: &mut Vec<GameEvent>) {
+        for seat in 0..self.seats.len() {
+            for hand in 0..self.seats[seat].hands.len() {
+                let (outcome, payout) = settle_hand(&self.seats[seat].hands[hand], &self.dealer, &self.rules);
+                let player = &mut self.seats[seat];
+                player.hands[hand].outcome = Some(outcome);
+                player.hands[hand].payout = payout;
+                player.bankroll += payout;
+                events.push(GameEvent::Settled { seat, hand, outcome, payout });
+            }
+        }
+        self.set_phase(Phase::Settlement, events);
+    }
+
+    /// Clears the table and goes back to taking bets, shuffling if the cut card came out
+    pub fn next_round(&mut self) -> Result<Vec<GameEvent>, GameError> {
+        self.expect_phase(Phase::Settlement)?;
+
+        let mut events = Vec::new();
+        for seat in &mut self.seats {
+            seat.clear_round();
+        }
+        self.dealer.clear();
+        self.hole_card_hidden = false;
+        self.active_seat = 0;
+        self.active = 0;
+        self.shoe.collect();
+        if self.shoe.reshuffle_if_needed() {
+            events.push(GameEvent::Shuffled);
+        }
+        self.set_phase(Phase::Betting, &mut events);
+        Ok(events)
+    }
+}
+
+// Works out how a finished hand did against the dealer and how many chips go back to the player
+fn settle_hand(player: &PlayerHand, dealer: &Hand, rules: &TableRules) -> (Outcome, u32) {
+    let bet = player.bet;
+    let dealer_blackjack = dealer.is_blackjack();
+    if player.even_money {
+        return (Outcome::Win, bet * 2);
+    }
+    if player.surrendered {
+        // Late surrender only counts once the dealer is known not to have blackjack
+        if dealer_blackjack && rules.surrender == Surrender::Late {
+            return (Outcome::Lose, 0);
+        }
+        return (Outcome::Surrender, bet / 2);
+    }
+    if player.hand.is_bust() {
+        return (Outcome::Bust, 0);
+    }
+
+    if player.is_natural() {
+        if dealer_blackjack {
+            return (Outcome::Push, bet);
+        }
+        return (Outcome::Blackjack, bet + rules.blackjack_payout.winnings(bet));
+    }
+    if dealer_blackjack {
+        return (Outcome::Lose, 0);
+    }
+
+    let total = player.hand.best_total();
+    let dealer_total = dealer.best_total();
+    if dealer.is_bust() || total > dealer_total {
+        (Outcome::Win, bet * 2)
+    } else if total == dealer_total {
+        (Outcome::Push, bet)
+    } else {
+        (Outcome::Lose, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::card::Rank;
+    use crate::game::rules::BlackjackPayout;
+
+    fn cards(text: &str) -> Vec<Card> {
+        text.split_whitespace().map(|card| card.parse().unwrap()).collect()
+    }
+
+    // A one seat game with 10 bet and the shoe stacked so these cards come out first.
+    // The deal order is player, dealer up card, player, hole card.
+    fn stacked(rules: TableRules, order: &str) -> BlackjackGame {
+        let mut game = BlackjackGame::new(rules, 1000, 1);
+        game.shoe.stack(&cards(order));
+        game.place_bet(10).unwrap();
+        game
+    }
+
+    fn player_hand(text: &str, bet: u32) -> PlayerHand {
+        let mut hand = PlayerHand::new(bet);
+        hand.hand = Hand::from_cards(&cards(text));
+        hand
+    }
+
+    #[test]
+    fn a_round_goes_through_every_phase() {
+        let mut game = stacked(TableRules::default(), "10H 9S 9D 9C");
+        assert_eq!(game.phase(), Phase::Betting);
+        assert_eq!(game.bankroll(), 990);
+        game.deal().unwrap();
+        assert_eq!(game.phase(), Phase::PlayerTurn);
+        assert_eq!(game.active_hand(), Some(0));
+        game.stand().unwrap();
+        assert_eq!(game.phase(), Phase::DealerTurn);
+        game.play_dealer().unwrap();
+        assert_eq!(game.phase(), Phase::Settlement);
+        assert_eq!(game.hands()[0].outcome, Some(Outcome::Win));
+        assert_eq!(game.bankroll(), 1010);
+        game.next_round().unwrap();
+        assert_eq!(game.phase(), Phase::Betting);
+        assert!(game.hands().is_empty());
+    }
+
+    #[test]
+    fn actions_out_of_turn_are_refused_and_change_nothing() {
+        let mut game = BlackjackGame::new(TableRules::default(), 100, 1);
+        assert_eq!(game.hit(), Err(GameError::WrongPhase(Phase::Betting)));
+        assert_eq!(game.play_dealer(), Err(GameError::WrongPhase(Phase::Betting)));
+        assert_eq!(game.next_round(), Err(GameError::WrongPhase(Phase::Betting)));
+        assert_eq!(game.phase(), Phase::Betting);
+        assert_eq!(game.bankroll(), 100);
+
+        let mut game = stacked(TableRules::default(), "10H 9S 7D 8C");
+        game.deal().unwrap();
+        assert_eq!(game.place_bet(10), Err(GameError::WrongPhase(Phase::PlayerTurn)));
+        assert_eq!(game.deal(), Err(GameError::WrongPhase(Phase::PlayerTurn)));
+        assert_eq!(game.next_round(), Err(GameError::WrongPhase(Phase::PlayerTurn)));
+        assert_eq!(game.bankroll(), 990);
+    }
+
+    #[test]
+    fn blackjack_pays_by_the_table_rules() {
+        let dealer = Hand::from_cards(&cards("10S 8H"));
+        let natural = player_hand("AS KD", 10);
+        assert_eq!(settle_hand(&natural, &dealer, &TableRules::default()), (Outcome::Blackjack, 25));
+        let six_to_five = TableRules {
+            blackjack_payout: BlackjackPayout::SixToFive,
+            ..Default::default()
+        };
+        assert_eq!(settle_hand(&natural, &dealer, &six_to_five), (Outcome::Blackjack, 22));
+    }
+
+    #[test]
+    fn split_21_is_not_a_blackjack() {
+        let dealer = Hand::from_cards(&cards("10S 8H"));
+        let mut hand = player_hand("AS KD", 10);
+        hand.from_split = true;
+        assert_eq!(settle_hand(&hand, &dealer, &TableRules::default()), (Outcome::Win, 20));
+    }
+
+    #[test]
+    fn pushes_busts_and_surrenders_pay_back_the_right_chips() {
+        let rules = TableRules::default();
+        let dealer = Hand::from_cards(&cards("10S 8H"));
+        assert_eq!(settle_hand(&player_hand("9S 9D", 10), &dealer, &rules), (Outcome::Push, 10));
+        assert_eq!(settle_hand(&player_hand("10C 6D 9H", 10), &dealer, &rules), (Outcome::Bust, 0));
+        assert_eq!(settle_hand(&player_hand("10C 7D", 10), &dealer, &rules), (Outcome::Lose, 0));
+        let mut surrendered = player_hand("10C 6D", 10);
+        surrendered.surrendered = true;
+        assert_eq!(settle_hand(&surrendered, &dealer, &rules), (Outcome::Surrender, 5));
+
+        // A player bust loses even when the dealer busts too
+        let dealer_bust = Hand::from_cards(&cards("10S 6H 8D"));
+        assert_eq!(settle_hand(&player_hand("10C 6D 9H", 10), &dealer_bust, &rules), (Outcome::Bust, 0));
+        assert_eq!(settle_hand(&player_hand("10C 2D", 10), &dealer_bust, &rules), (Outcome::Win, 20));
+    }
+
+    #[test]
+    fn late_surrender_loses_everything_to_a_dealer_blackjack() {
+        let dealer = Hand::from_cards(&cards("AS KH"));
+        let mut surrendered = player_hand("10C 6D", 10);
+        surrendered.surrendered = true;
+        assert_eq!(settle_hand(&surrendered, &dealer, &TableRules::default()), (Outcome::Lose, 0));
+        let early = TableRules {
+            surrender: Surrender::Early,
+            ..Default::default()
+        };
+        assert_eq!(settle_hand(&surrendered, &dealer, &early), (Outcome::Surrender, 5));
+    }
+
+    #[test]
+    fn h17_dealer_hits_soft_17() {
+        let mut game = stacked(TableRules::default(), "10H 6S 10D AC 4H");
+        game.deal().unwrap();
+        game.stand().unwrap();
+        game.play_dealer().unwrap();
+        assert_eq!(game.dealer().len(), 3);
+        assert_eq!(game.hands()[0].outcome, Some(Outcome::Lose));
+    }
+
+    #[test]
+    fn s17_dealer_stands_on_soft_17() {
+        let rules = TableRules {
+            dealer_hits_soft_17: false,
+            ..Default::default()
+        };
+        let mut game = stacked(rules, "10H 6S 10D AC 4H");
+        game.deal().unwrap();
+        game.stand().unwrap();
+        game.play_dealer().unwrap();
+        assert_eq!(game.dealer().len(), 2);
+        assert_eq!(game.hands()[0].outcome, Some(Outcome::Win));
+    }
+
+    #[test]
+    fn deal_goes_through_dealing() {
+        let mut game = stacked(TableRules::default(), "10H 9S 7D 8C");
+        let events = game.deal().unwrap();
+        assert_eq!(events[0], GameEvent::PhaseChanged(Phase::Dealing));
+        assert_eq!(events.last(), Some(&GameEvent::ActiveHand { seat: 0, hand: 0 }));
+        assert_eq!(game.phase(), Phase::PlayerTurn);
+        assert_eq!(game.deal(), Err(GameError::WrongPhase(Phase::PlayerTurn)));
+    }
+
+    #[test]
+    fn a_shoe_running_out_mid_round_is_announced() {
+        let rules = TableRules {
+            decks: 1,
+            ..Default::default()
+        };
+        let mut game = BlackjackGame::new(rules, 1000, 1);
+        while game.shoe.cards_remaining() > 2 {
+            game.shoe.deal();
+        }
+        game.shoe.collect();
+        game.place_bet(10).unwrap();
+        let events = game.deal().unwrap();
+
+        // Two cards come from the old shoe, the rest from the new one
+        let shuffled = events.iter().position(|event| *event == GameEvent::Shuffled).unwrap();
+        let before = events[..shuffled]
+            .iter()
+            .filter(|event| matches!(event, GameEvent::CardDealt { .. }))
+            .count();
+        assert_eq!(before, 2);
+        assert_eq!(game.shoe().shuffle_number(), 1);
+    }
+
+    #[test]
+    fn dealer_peeks_under_a_ten() {
+        let mut game = stacked(TableRules::default(), "10H KS 7D AC");
+        let events = game.deal().unwrap();
+        assert!(events.contains(&GameEvent::DealerBlackjack));
+        assert_eq!(game.phase(), Phase::Settlement);
+        assert_eq!(game.hands()[0].outcome, Some(Outcome::Lose));
+
+        let mut game = stacked(TableRules::default(), "10H KS 7D 9C");
+        game.deal().unwrap();
+        assert_eq!(game.phase(), Phase::PlayerTurn);
+        assert!(game.hole_card_hidden());
+    }
+
+    #[test]
+    fn bets_outside_the_limits_or_the_chips_are_refused() {
+        let mut game = BlackjackGame::new(TableRules::default(), 100, 1);
+        assert_eq!(game.place_bet(0), Err(GameError::Bet(BetError::BelowMinimum(5))));
+        assert_eq!(game.place_bet(200), Err(GameError::Bet(BetError::InsufficientFunds(100))));
+        game.place_bet(100).unwrap();
+        // The earlier bet counts towards what the seat has when it is replaced
+        game.place_bet(50).unwrap();
+        assert_eq!(game.bankroll(), 50);
+        assert_eq!(
+            game.place_side_bet_at(0, SideBet::PerfectPairs, 60),
+            Err(GameError::Bet(BetError::InsufficientFunds(50)))
+        );
+    }
+
+    #[test]
+    fn side_bets_keep_to_the_table_limits() {
+        let mut game = BlackjackGame::new(TableRules::default(), 1_000_000, 1);
+        let max = game.rules().limits.max_bet;
+        assert_eq!(
+            game.place_side_bet_at(0, SideBet::PerfectPairs, max + 1),
+            Err(GameError::Bet(BetError::AboveMaximum(max)))
+        );
+        assert_eq!(
+            game.place_side_bet_at(0, SideBet::TwentyOnePlusThree, 1),
+            Err(GameError::Bet(BetError::BelowMinimum(5)))
+        );
+        game.place_side_bet_at(0, SideBet::PerfectPairs, max).unwrap();
+        game.place_side_bet_at(0, SideBet::PerfectPairs, 0).unwrap();
+        assert_eq!(game.bankroll(), 1_000_000);
+    }
+
+    #[test]
+    fn side_bets_are_paid_straight_after_the_deal() {
+        let mut game = stacked(TableRules::default(), "8H 10S 8D 7C");
+        game.place_side_bet_at(0, SideBet::PerfectPairs, 5).unwrap();
+        game.place_side_bet_at(0, SideBet::TwentyOnePlusThree, 5).unwrap();
+        game.deal().unwrap();
+        let stakes = game.seats()[0].side_bets();
+        assert_eq!(stakes[0].result, Some(SideBetResult::ColoredPair));
+        assert_eq!(stakes[0].payout, 65);
+        assert_eq!(stakes[1].result, None);
+        assert_eq!(stakes[1].payout, 0);
+        assert_eq!(game.bankroll(), 1000 - 20 + 65);
+    }
+
+    #[test]
+    fn doubling_doubles_the_bet_for_one_more_card() {
+        let mut game = stacked(TableRules::default(), "6H 9S 5D 8C 10C");
+        game.deal().unwrap();
+        assert!(game.can_double());
+        game.double().unwrap();
+        assert_eq!(game.hands()[0].bet, 20);
+        assert_eq!(game.hands()[0].hand.len(), 3);
+        assert_eq!(game.phase(), Phase::DealerTurn);
+        game.play_dealer().unwrap();
+        assert_eq!(game.hands()[0].outcome, Some(Outcome::Win));
+        assert_eq!(game.bankroll(), 1000 - 20 + 40);
+
+        // Only the first two cards can be doubled
+        let mut game = stacked(TableRules::default(), "2H 9S 3D 8C 4C");
+        game.deal().unwrap();
+        game.hit().unwrap();
+        assert!(!game.can_double());
+        assert_eq!(game.double(), Err(GameError::CannotDouble));
+    }
+
+    #[test]
+    fn resplits_stop_at_the_most_hands() {
+        let mut game = stacked(TableRules::default(), "8H 10S 8D 7C 8C 8S 8H 2D 3D 4D");
+        game.deal().unwrap();
+        for hands in 2..=4 {
+            assert!(game.can_split());
+            game.split().unwrap();
+            assert_eq!(game.hands().len(), hands);
+        }
+        // The first hand is a pair of eights again but the table allows only 4 hands
+        assert_eq!(game.hands()[0].hand.cards()[1].rank, Rank::Eight);
+        assert!(!game.can_split());
+        assert_eq!(game.split(), Err(GameError::CannotSplit));
+        assert_eq!(game.bankroll(), 1000 - 40);
+    }
+
+    #[test]
+    fn split_aces_get_one_card_each() {
+        let mut game = stacked(TableRules::default(), "AH 10S AD 7C AC 9H");
+        game.deal().unwrap();
+        game.split().unwrap();
+        // Without resplitting the second ace stands, both hands are played at once
+        assert_eq!(game.phase(), Phase::DealerTurn);
+        assert!(game.hands().iter().all(|hand| hand.hand.len() == 2 && hand.split_aces));
+    }
+
+    #[test]
+    fn split_aces_can_be_split_again_with_rsa() {
+        let rules = TableRules {
+            resplit_aces: true,
+            ..Default::default()
+        };
+        let mut game = stacked(rules, "AH 10S AD 7C AC 9H");
+        game.deal().unwrap();
+        game.split().unwrap();
+        assert_eq!(game.phase(), Phase::PlayerTurn);
+        assert!(!game.can_hit());
+        assert!(!game.can_double());
+        assert!(game.can_split());
+    }
+
+    #[test]
+    fn insurance_pays_2_to_1_on_a_dealer_blackjack() {
+        let mut game = stacked(TableRules::default(), "10H AS 9D KC");
+        game.deal().unwrap();
+        assert_eq!(game.phase(), Phase::Insurance);
+        assert_eq!(game.max_insurance(), 5);
+        assert_eq!(game.take_insurance(6), Err(GameError::InvalidInsurance));
+        game.take_insurance(5).unwrap();
+        assert_eq!(game.phase(), Phase::Settlement);
+        // The hand is lost and the insurance wins it back
+        assert_eq!(game.total_payout(), 15);
+        assert_eq!(game.bankroll(), 1000);
+    }
+
+    #[test]
+    fn insurance_is_lost_when_the_dealer_has_no_blackjack() {
+        let mut game = stacked(TableRules::default(), "10H AS 9D 7C");
+        game.deal().unwrap();
+        game.take_insurance(5).unwrap();
+        assert_eq!(game.phase(), Phase::PlayerTurn);
+        assert_eq!(game.bankroll(), 1000 - 15);
+    }
+
+    #[test]
+    fn even_money_pays_1_to_1_either_way() {
+        for hole in ["KC", "7C"] {
+            let mut game = stacked(TableRules::default(), &format!("AH AS KD {}", hole));
+            game.deal().unwrap();
+            assert!(game.can_take_even_money());
+            game.take_even_money().unwrap();
+            if game.phase() == Phase::DealerTurn {
+                game.play_dealer().unwrap();
+            }
+            assert_eq!(game.phase(), Phase::Settlement);
+            assert_eq!(game.hands()[0].outcome, Some(Outcome::Win));
+            assert_eq!(game.bankroll(), 1010);
+        }
+    }
+
+    #[test]
+    fn late_surrender_waits_for_the_peek() {
+        let mut game = stacked(TableRules::default(), "10H AS 6D 7C");
+        game.deal().unwrap();
+        assert!(!game.can_surrender());
+        assert_eq!(game.surrender(), Err(GameError::CannotSurrender));
+        game.decline_insurance().unwrap();
+        assert!(game.can_surrender());
+        game.surrender().unwrap();
+        game.play_dealer().unwrap();
+        assert_eq!(game.hands()[0].outcome, Some(Outcome::Surrender));
+        assert_eq!(game.bankroll(), 995);
+    }
+
+    #[test]
+    fn early_surrender_beats_the_peek() {
+        let rules = TableRules {
+            surrender: Surrender::Early,
+            ..Default::default()
+        };
+        let mut game = stacked(rules, "10H AS 6D KC");
+        game.deal().unwrap();
+        assert!(game.can_surrender());
+        game.surrender().unwrap();
+        assert_eq!(game.phase(), Phase::Settlement);
+        assert_eq!(game.hands()[0].outcome, Some(Outcome::Surrender));
+        assert_eq!(game.bankroll(), 995);
+    }
+
+    #[test]
+    fn early_surrender_against_a_ten_comes_before_the_peek() {
+        let rules = TableRules {
+            surrender: Surrender::Early,
+            ..Default::default()
+        };
+        let mut game = stacked(rules.clone(), "10H KS 6D AC");
+        game.deal().unwrap();
+        assert_eq!(game.phase(), Phase::Dealing);
+        assert_eq!(game.active_seat(), Some(0));
+        assert!(game.hole_card_hidden());
+        assert_eq!(game.hit(), Err(GameError::WrongPhase(Phase::Dealing)));
+        game.surrender().unwrap();
+        assert_eq!(game.phase(), Phase::Settlement);
+        assert_eq!(game.hands()[0].outcome, Some(Outcome::Surrender));
+        assert_eq!(game.bankroll(), 995);
+
+        // Keeping the hand lets the dealer peek, and play starts when there is no blackjack
+        let mut game = stacked(rules, "10H KS 6D 9C");
+        game.deal().unwrap();
+        game.decline_surrender().unwrap();
+        assert_eq!(game.phase(), Phase::PlayerTurn);
+        assert_eq!(game.decline_surrender(), Err(GameError::WrongPhase(Phase::PlayerTurn)));
+    }
+
+    #[test]
+    fn no_surrender_after_hitting_or_at_a_no_surrender_table() {
+        let mut game = stacked(TableRules::default(), "10H 9S 2D 7C 3C");
+        game.deal().unwrap();
+        game.hit().unwrap();
+        assert!(!game.can_surrender());
+
+        let rules = TableRules {
+            surrender: Surrender::None,
+            ..Default::default()
+        };
+        let mut game = stacked(rules, "10H 9S 6D 7C");
+        game.deal().unwrap();
+        assert!(!game.can_surrender());
+    }
+}
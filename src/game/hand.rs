@@ -0,0 +1,171 @@
+/*
+Program Details: A hand of cards with blackjack totals
+
+In your game/mod.rs file add the following:
+    pub mod hand;
+
+Then with the other use commands add:
+use blackjack3::game::hand::Hand;
+
+Usage examples:
+    let mut hand = Hand::new();
+    hand.push(card);
+    hand.best_total();   // highest total that is 21 or under if there is one
+    hand.is_soft();      // true when an ace is being counted as 11
+    hand.is_bust();
+
+Totals:
+    hard_total() counts every ace as 1.
+    soft_total() counts one ace as 11 (the same as hard_total() when there are no aces).
+    best_total() is the soft total unless that would bust, then the hard total.
+Only one ace can ever count as 11, two would already be 22.
+*/
+use std::fmt;
+
+use crate::game::card::Card;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Hand {
+    cards: Vec<Card>,
+}
+
+impl Hand {
+    pub fn new() -> Self {
+        Self { cards: Vec::new() }
+    }
+
+    pub fn from_cards(cards: &[Card]) -> Self {
+        Self { cards: cards.to_vec() }
+    }
+
+    pub fn push(&mut self, card: Card) {
+        self.cards.push(card);
+    }
+
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.cards.clear();
+    }
+
+    fn has_ace(&self) -> bool {
+        self.cards.iter().any(|card| card.rank.is_ace())
+    }
+
+    /// Total with every ace counted as 1
+    pub fn hard_total(&self) -> u8 {
+        self.cards.iter().map(|card| card.rank.value()).sum()
+    }
+
+    /// Total with one ace counted as 11, can be over 21
+    pub fn soft_total(&self) -> u8 {
+        if self.has_ace() {
+            self.hard_total() + 10
+        } else {
+            self.hard_total()
+        }
+    }
+
+    /// The total the hand plays as
+    pub fn best_total(&self) -> u8 {
+        if self.is_soft() {
+            self.soft_total()
+        } else {
+            self.hard_total()
+        }
+    }
+
+    /// True when an ace is counted as 11 without busting
+    pub fn is_soft(&self) -> bool {
+        self.has_ace() && self.soft_total() <= 21
+    }
+
+    /// 21 with the first two cards
+    pub fn is_blackjack(&self) -> bool {
+        self.cards.len() == 2 && self.best_total() == 21
+    }
+
+    pub fn is_bust(&self) -> bool {
+        self.hard_total() > 21
+    }
+}
+
+impl fmt::Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, card) in self.cards.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", card)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hand(text: &str) -> Hand {
+        let cards: Vec<Card> = text.split_whitespace().map(|card| card.parse().unwrap()).collect();
+        Hand::from_cards(&cards)
+    }
+
+    #[test]
+    fn an_ace_counts_as_eleven_until_it_would_bust() {
+        let soft_17 = hand("AH 6C");
+        assert_eq!((soft_17.hard_total(), soft_17.soft_total(), soft_17.best_total()), (7, 17, 17));
+        assert!(soft_17.is_soft());
+
+        let hard_16 = hand("AH 5C 10D");
+        assert_eq!((hard_16.hard_total(), hard_16.soft_total(), hard_16.best_total()), (16, 26, 16));
+        assert!(!hard_16.is_soft());
+        assert!(!hard_16.is_bust());
+    }
+
+    #[test]
+    fn only_one_ace_counts_as_eleven() {
+        let soft_21 = hand("AH AC 9D");
+        assert_eq!(soft_21.best_total(), 21);
+        assert!(soft_21.is_soft());
+        assert!(!soft_21.is_blackjack());
+
+        let soft_12 = hand("AH AC");
+        assert_eq!(soft_12.best_total(), 12);
+        assert!(soft_12.is_soft());
+    }
+
+    #[test]
+    fn blackjack_is_21_on_two_cards() {
+        assert!(hand("AS KD").is_blackjack());
+        assert!(hand("10C AH").is_blackjack());
+        assert!(!hand("7S 7D 7C").is_blackjack());
+        assert!(!hand("KS QD").is_blackjack());
+    }
+
+    #[test]
+    fn over_21_is_bust() {
+        let bust = hand("KS QD 2C");
+        assert_eq!(bust.best_total(), 22);
+        assert!(bust.is_bust());
+        assert!(!hand("KS QD AC").is_bust());
+        assert!(hand("AS KD QC 5H").is_bust());
+    }
+
+    #[test]
+    fn a_hand_prints_its_cards() {
+        assert_eq!(hand("AS 10D").to_string(), "AS 10D");
+        assert_eq!(Hand::new().to_string(), "");
+        assert!(Hand::new().is_empty());
+    }
+}
@@ -0,0 +1,296 @@
+/*
+Program Details: Hand history log
+
+Appends one record per settled round to two files side by side, a JSON Lines
+file (one JSON object per line) and a CSV file with a header row, so a session
+can be loaded straight into a notebook or a spreadsheet.
+
+In your game/mod.rs file add the following:
+    pub mod history;
+
+Then with the other use commands add:
+use blackjack3::game::history::{HistoryEntry, HistoryWriter};
+
+Usage examples:
+    // Writes history/sam.jsonl and history/sam.csv
+    let writer = HistoryWriter::new("history/sam");
+
+    // Once the round is settled, for the player's seat
+    if let Some(entry) = HistoryEntry::from_game(&game, seat, timestamp) {
+        writer.append(&entry)?;
+    }
+
+Columns (CSV) and keys (JSON):
+    timestamp     seconds since 1970 when the round was settled
+    seat          which seat at the table the player sat in, 0 is the first
+    seed          shoe seed, shuffle and position are where in the shoe the round started
+    bet           the seat's first bet, before doubles and splits
+    player_cards  cards of each of the seat's hands, like "AH 10D" (CSV: hands split by " | ")
+    dealer_cards  the dealer's whole hand
+    actions       the player's decisions in order (CSV: split by "; ")
+    outcomes      result of each hand
+    hand_bets     chips on each hand at the end, a doubled hand shows twice the bet
+    insurance     insurance bet, 0 for none
+    payout        chips paid back, stakes included
+    net           payout minus everything staked
+    bankroll      chips after the round
+    side_bets     each side bet with its stake and result, like "21+3 5 flush pays 30" (CSV: split by "; ")
+*/
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::engine::{BlackjackGame, Phase};
+
+/// One settled round
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub timestamp: u64,
+    #[serde(default)]
+    pub seat: usize,
+    pub seed: u64,
+    pub shuffle: u64,
+    pub position: usize,
+    pub bet: u32,
+    pub player_cards: Vec<Vec<String>>,
+    pub dealer_cards: Vec<String>,
+    pub actions: Vec<String>,
+    pub outcomes: Vec<String>,
+    #[serde(default)]
+    pub hand_bets: Vec<u32>,
+    pub insurance: u32,
+    pub payout: u32,
+    pub net: i64,
+    pub bankroll: u32,
+    #[serde(default)]
+    pub side_bets: Vec<String>,
+}
+
+const CSV_HEADER: &str =
+    "timestamp,seat,seed,shuffle,position,bet,player_cards,dealer_cards,actions,outcomes,hand_bets,insurance,payout,net,bankroll,side_bets";
+
+impl HistoryEntry {
+    /// How the seat did in the round the game just settled, None when it is not settled yet
+    /// or the seat sat the round out
+    pub fn from_game(game: &BlackjackGame, seat: usize, timestamp: u64) -> Option<Self> {
+        if game.phase() != Phase::Settlement {
+            return None;
+        }
+        let record = game.round_record()?;
+        let player = game.seats().get(seat).filter(|player| player.is_playing())?;
+        Some(Self {
+            timestamp,
+            seat,
+            seed: record.seed,
+            shuffle: record.shuffle,
+            position: record.position,
+            bet: record.bets[seat],
+            player_cards: player
+                .hands()
+                .iter()
+                .map(|hand| hand.hand.cards().iter().map(|card| card.to_string()).collect())
+                .collect(),
+            dealer_cards: game.dealer().cards().iter().map(|card| card.to_string()).collect(),
+            actions: record
+                .actions
+                .iter()
+                .filter(|(by, _)| *by == seat)
+                .map(|(_, action)| action.to_string())
+                .collect(),
+            outcomes: player
+                .hands()
+                .iter()
+                .map(|hand| hand.outcome.map(|outcome| format!("{:?}", outcome).to_lowercase()).unwrap_or_default())
+                .collect(),
+            hand_bets: player.hands().iter().map(|hand| hand.bet).collect(),
+            insurance: player.insurance(),
+            payout: player.total_payout(),
+            net: player.total_payout() as i64 - player.total_staked() as i64,
+            bankroll: player.bankroll(),
+            side_bets: player
+                .side_bets()
+                .iter()
+                .map(|stake| match stake.result {
+                    Some(result) => format!(
+                        "{} {} {} pays {}",
+                        stake.bet,
+                        stake.amount,
+                        result.to_string().to_lowercase(),
+                        stake.payout
+                    ),
+                    None => format!("{} {} lost", stake.bet, stake.amount),
+                })
+                .collect(),
+        })
+    }
+
+    /// The entry as one CSV row, in the same order as the header
+    pub fn to_csv(&self) -> String {
+        let hands: Vec<String> = self.player_cards.iter().map(|cards| cards.join(" ")).collect();
+        [
+            self.timestamp.to_string(),
+            self.seat.to_string(),
+            self.seed.to_string(),
+            self.shuffle.to_string(),
+            self.position.to_string(),
+            self.bet.to_string(),
+            csv_field(&hands.join(" | ")),
+            csv_field(&self.dealer_cards.join(" ")),
+            csv_field(&self.actions.join("; ")),
+            csv_field(&self.outcomes.join(" | ")),
+            csv_field(&self.hand_bets.iter().map(|bet| bet.to_string()).collect::<Vec<_>>().join(" | ")),
+            self.insurance.to_string(),
+            self.payout.to_string(),
+            self.net.to_string(),
+            self.bankroll.to_string(),
+            csv_field(&self.side_bets.join("; ")),
+        ]
+        .join(",")
+    }
+}
+
+// Quotes a field that has a comma, quote or new line in it
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Appends entries to <path>.jsonl and <path>.csv
+#[derive(Debug, Clone)]
+pub struct HistoryWriter {
+    jsonl: PathBuf,
+    csv: PathBuf,
+}
+
+impl HistoryWriter {
+    /// Any extension on the path is replaced with .jsonl and .csv
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        Self {
+            jsonl: path.with_extension("jsonl"),
+            csv: path.with_extension("csv"),
+        }
+    }
+
+    /// History for a profile in the user's data folder, next to the profiles
+    pub fn open_default(profile_name: &str) -> Self {
+        let base = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+        let file = profile_name.trim().to_lowercase().replace(' ', "_");
+        Self::new(base.join("blackjack3").join("history").join(file))
+    }
+
+    pub fn jsonl_path(&self) -> &Path {
+        &self.jsonl
+    }
+
+    pub fn csv_path(&self) -> &Path {
+        &self.csv
+    }
+
+    /// Adds the entry to the end of both files, making them (and the CSV header) if needed
+    pub fn append(&self, entry: &HistoryEntry) -> io::Result<()> {
+        if let Some(dir) = self.jsonl.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut jsonl = OpenOptions::new().create(true).append(true).open(&self.jsonl)?;
+        writeln!(jsonl, "{}", serde_json::to_string(entry)?)?;
+
+        let mut csv = OpenOptions::new().create(true).append(true).open(&self.csv)?;
+        if csv.metadata()?.len() == 0 {
+            writeln!(csv, "{}", CSV_HEADER)?;
+        }
+        writeln!(csv, "{}", entry.to_csv())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::rules::TableRules;
+
+    fn entry() -> HistoryEntry {
+        HistoryEntry {
+            timestamp: 1700000000,
+            seat: 0,
+            seed: 7,
+            shuffle: 0,
+            position: 0,
+            bet: 10,
+            player_cards: vec![vec!["AH".to_string(), "10D".to_string()]],
+            dealer_cards: vec!["9C".to_string(), "8S".to_string()],
+            actions: Vec::new(),
+            outcomes: vec!["blackjack".to_string()],
+            hand_bets: vec![10],
+            insurance: 0,
+            payout: 25,
+            net: 15,
+            bankroll: 1015,
+            side_bets: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn fields_with_commas_or_quotes_are_quoted() {
+        assert_eq!(csv_field("hit; stand"), "hit; stand");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn the_csv_header_is_written_once() {
+        let dir = std::env::temp_dir().join(format!("blackjack3-history-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let writer = HistoryWriter::new(dir.join("sam"));
+        writer.append(&entry()).unwrap();
+        writer.append(&entry()).unwrap();
+
+        let csv = fs::read_to_string(writer.csv_path()).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines, [CSV_HEADER, &entry().to_csv(), &entry().to_csv()]);
+        assert_eq!(lines[1].split(',').count(), CSV_HEADER.split(',').count());
+
+        let jsonl = fs::read_to_string(writer.jsonl_path()).unwrap();
+        let read: Vec<HistoryEntry> = jsonl.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(read, [entry(), entry()]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn from_game_describes_the_settled_round() {
+        let mut game = BlackjackGame::new(TableRules::default(), 1000, 3);
+        let seat = game.add_seat(1000).unwrap();
+        game.place_bet(10).unwrap();
+        game.deal().unwrap();
+        assert_eq!(HistoryEntry::from_game(&game, 0, 1), None);
+        while game.phase() != Phase::Settlement {
+            match game.phase() {
+                Phase::Insurance => game.decline_insurance().unwrap(),
+                Phase::PlayerTurn => game.stand().unwrap(),
+                _ => game.play_dealer().unwrap(),
+            };
+        }
+
+        let entry = HistoryEntry::from_game(&game, 0, 1).unwrap();
+        let player = &game.seats()[0];
+        assert_eq!((entry.timestamp, entry.seat, entry.seed, entry.bet), (1, 0, 3, 10));
+        assert_eq!(
+            entry.player_cards[0],
+            player.hands()[0].hand.cards().iter().map(|card| card.to_string()).collect::<Vec<_>>()
+        );
+        assert_eq!(entry.dealer_cards.len(), game.dealer().len());
+        assert_eq!(entry.hand_bets, [10]);
+        assert_eq!(entry.outcomes.len(), 1);
+        assert_eq!(entry.net, player.bankroll() as i64 - 1000);
+        assert_eq!(entry.bankroll, player.bankroll());
+        // The second seat did not bet
+        assert_eq!(HistoryEntry::from_game(&game, seat, 1), None);
+    }
+}
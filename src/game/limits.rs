@@ -0,0 +1,201 @@
+/*
+Program Details: Table limits and the tables in the lobby
+
+In your game/mod.rs file add the following:
+    pub mod limits;
+
+Then with the other use commands add:
+use blackjack3::game::limits::{BetError, TableLimits, LOBBY};
+
+Usage examples:
+    let limits = TableLimits::new(5, 500);
+    limits.spread();                    // 100, the biggest bet is 100 times the smallest
+    limits.parse_bet("25", chips)?;     // Ok(25)
+    limits.parse_bet("abc", chips);     // Err(BetError::NotANumber)
+    limits.check(1000, chips);          // Err(BetError::AboveMaximum(500))
+
+    // The tables a player can pick from before they sit down
+    for table in LOBBY.iter() {
+        println!("{} {}", table.name, table.limits);   // "$5 Table 5-500"
+    }
+
+The limits are part of the table rules, the engine refuses main bets outside
+them. Side bets and insurance do not count towards the limits.
+*/
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Why a bet was refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BetError {
+    /// The table minimum
+    BelowMinimum(u32),
+    /// The table maximum
+    AboveMaximum(u32),
+    /// The chips the player has
+    InsufficientFunds(u32),
+    NotANumber,
+}
+
+impl fmt::Display for BetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BetError::BelowMinimum(min) => write!(f, "The table minimum is {}", min),
+            BetError::AboveMaximum(max) => write!(f, "The table maximum is {}", max),
+            BetError::InsufficientFunds(chips) => write!(f, "Not Enough Chips, you have {}", chips),
+            BetError::NotANumber => write!(f, "Bets are whole numbers of chips"),
+        }
+    }
+}
+
+impl std::error::Error for BetError {}
+
+/// Smallest and biggest main bet at a table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TableLimits {
+    /// (default: 5)
+    pub min_bet: u32,
+    /// (default: 500)
+    pub max_bet: u32,
+}
+
+impl Default for TableLimits {
+    fn default() -> Self {
+        Self::new(5, 500)
+    }
+}
+
+impl TableLimits {
+    /// No maximum and a minimum of one chip, for simulations
+    pub const NONE: TableLimits = TableLimits::new(1, u32::MAX);
+
+    pub const fn new(min_bet: u32, max_bet: u32) -> Self {
+        Self { min_bet, max_bet }
+    }
+
+    /// How many times the minimum the maximum is, tables with a big spread suit players who raise their bets
+    pub fn spread(&self) -> u32 {
+        self.max_bet / self.min_bet.max(1)
+    }
+
+    /// Whether a bet fits the table and the player's chips
+    pub fn check(&self, amount: u32, chips: u32) -> Result<(), BetError> {
+        if amount < self.min_bet.max(1) {
+            Err(BetError::BelowMinimum(self.min_bet.max(1)))
+        } else if amount > self.max_bet {
+            Err(BetError::AboveMaximum(self.max_bet))
+        } else if amount > chips {
+            Err(BetError::InsufficientFunds(chips))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Reads a bet typed in by the player and checks it
+    pub fn parse_bet(&self, text: &str, chips: u32) -> Result<u32, BetError> {
+        let amount = text.trim().parse::<u32>().map_err(|_| BetError::NotANumber)?;
+        self.check(amount, chips)?;
+        Ok(amount)
+    }
+
+    /// Brings a bet inside the limits, it can still be more than the player has
+    pub fn clamp(&self, amount: u32) -> u32 {
+        amount.clamp(self.min_bet.max(1), self.max_bet.max(self.min_bet.max(1)))
+    }
+}
+
+impl fmt::Display for TableLimits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.max_bet == u32::MAX {
+            write!(f, "{} and up", self.min_bet)
+        } else {
+            write!(f, "{}-{}", self.min_bet, self.max_bet)
+        }
+    }
+}
+
+/// A table in the lobby
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LobbyTable {
+    pub name: &'static str,
+    pub limits: TableLimits,
+}
+
+/// The tables to choose from, cheapest first
+pub const LOBBY: [LobbyTable; 4] = [
+    LobbyTable {
+        name: "Penny Table",
+        limits: TableLimits::new(1, 100),
+    },
+    LobbyTable {
+        name: "$5 Table",
+        limits: TableLimits::new(5, 500),
+    },
+    LobbyTable {
+        name: "$25 Table",
+        limits: TableLimits::new(25, 2500),
+    },
+    LobbyTable {
+        name: "High Roller",
+        limits: TableLimits::new(100, 10000),
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: TableLimits = TableLimits::new(5, 500);
+
+    #[test]
+    fn check_edges() {
+        assert_eq!(LIMITS.check(0, 1000), Err(BetError::BelowMinimum(5)));
+        assert_eq!(LIMITS.check(4, 1000), Err(BetError::BelowMinimum(5)));
+        assert_eq!(LIMITS.check(5, 1000), Ok(()));
+        assert_eq!(LIMITS.check(500, 1000), Ok(()));
+        assert_eq!(LIMITS.check(501, 1000), Err(BetError::AboveMaximum(500)));
+        assert_eq!(LIMITS.check(100, 99), Err(BetError::InsufficientFunds(99)));
+        assert_eq!(LIMITS.check(100, 100), Ok(()));
+        // The table limits are reported before the chips
+        assert_eq!(LIMITS.check(501, 10), Err(BetError::AboveMaximum(500)));
+        assert_eq!(LIMITS.check(4, 0), Err(BetError::BelowMinimum(5)));
+    }
+
+    #[test]
+    fn zero_is_never_a_bet() {
+        assert_eq!(TableLimits::new(0, 100).check(0, 100), Err(BetError::BelowMinimum(1)));
+        assert_eq!(TableLimits::NONE.check(0, 100), Err(BetError::BelowMinimum(1)));
+        assert_eq!(TableLimits::NONE.check(1_000_000, 1_000_000), Ok(()));
+    }
+
+    #[test]
+    fn parse_bet_reads_whole_numbers() {
+        assert_eq!(LIMITS.parse_bet(" 25 ", 1000), Ok(25));
+        assert_eq!(LIMITS.parse_bet("", 1000), Err(BetError::NotANumber));
+        assert_eq!(LIMITS.parse_bet("abc", 1000), Err(BetError::NotANumber));
+        assert_eq!(LIMITS.parse_bet("-5", 1000), Err(BetError::NotANumber));
+        assert_eq!(LIMITS.parse_bet("2.5", 1000), Err(BetError::NotANumber));
+        assert_eq!(LIMITS.parse_bet("0", 1000), Err(BetError::BelowMinimum(5)));
+        assert_eq!(LIMITS.parse_bet("1000", 1000), Err(BetError::AboveMaximum(500)));
+        assert_eq!(LIMITS.parse_bet("400", 300), Err(BetError::InsufficientFunds(300)));
+    }
+
+    #[test]
+    fn clamp_brings_bets_inside_the_limits() {
+        assert_eq!(LIMITS.clamp(0), 5);
+        assert_eq!(LIMITS.clamp(4), 5);
+        assert_eq!(LIMITS.clamp(50), 50);
+        assert_eq!(LIMITS.clamp(501), 500);
+        assert_eq!(TableLimits::NONE.clamp(0), 1);
+        // A maximum under the minimum does not panic
+        assert_eq!(TableLimits::new(10, 5).clamp(7), 10);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(LIMITS.to_string(), "5-500");
+        assert_eq!(TableLimits::NONE.to_string(), "1 and up");
+    }
+}
@@ -0,0 +1,31 @@
+/*
+--------------------------------------------
+game/mod.rs
+This file lists the blackjack game logic modules.
+
+Nothing in here draws to the screen or reads input, so the rules can be
+used by anything (the GUI in main.rs, tests, scripts) without macroquad
+needing a window.
+
+Once listed here, you can import from main.rs (or any program in src/bin):
+use blackjack3::game::card::Card;
+--------------------------------------------
+*/
+// Add modules below
+pub mod ai;
+pub mod card;
+pub mod counting;
+pub mod engine;
+pub mod hand;
+pub mod history;
+pub mod limits;
+pub mod net;
+pub mod odds;
+pub mod profile;
+pub mod replay;
+pub mod rules;
+pub mod server;
+pub mod shoe;
+pub mod side_bets;
+pub mod stats;
+pub mod strategy;
@@ -0,0 +1,301 @@
+/*
+Program Details: Messages between the table server and its players on the local network
+
+Every message is JSON with a 4 byte (big endian) length in front of it, so the
+other side always knows where one message stops and the next one starts.
+
+In your game/mod.rs file add the following:
+    pub mod net;
+
+Then with the other use commands add:
+use blackjack3::game::net::{read_message, write_message, ClientMessage, ServerMessage};
+
+Usage examples:
+    let mut stream = TcpStream::connect("127.0.0.1:7777")?;
+    write_message(&mut stream, &ClientMessage::Join { name: "Sam".to_string() })?;
+    write_message(&mut stream, &ClientMessage::Bet { amount: 25 })?;
+
+    match read_message::<_, ServerMessage>(&mut stream)? {
+        ServerMessage::Table(table) => { ... }   // the whole table, sent after every change
+        ServerMessage::Error(text) => { ... }    // an action that was refused
+    }
+
+A client joins first, then bets and plays. Play messages only count when it
+is the sender's seat's turn, anybody seated can start the deal or the next round.
+*/
+use std::io::{self, Read, Write};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::game::card::Card;
+use crate::game::engine::{BlackjackGame, Outcome, Phase};
+
+/// Port the server listens on unless told otherwise
+pub const DEFAULT_PORT: u16 = 7777;
+
+/// Biggest message either side accepts, anything longer is a broken or hostile peer
+pub const MAX_MESSAGE_LEN: usize = 64 * 1024;
+
+/// What a player sends to the server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClientMessage {
+    /// Sit down at the table, the first message a client sends
+    Join {
+        name: String,
+    },
+    Bet {
+        amount: u32,
+    },
+    /// No more bets, deal the round to every seat that bet
+    Deal,
+    Hit,
+    Stand,
+    Double,
+    Split,
+    Surrender,
+    Insurance {
+        amount: u32,
+    },
+    EvenMoney,
+    DeclineInsurance,
+    /// Keep the hand when early surrender is offered against a ten
+    DeclineSurrender,
+    /// Clear the settled round away for the next one
+    NextRound,
+    /// A fresh stack of chips for a player who went broke
+    Rebuy,
+    Leave,
+}
+
+/// What the server sends to a player
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ServerMessage {
+    Table(TableState),
+    Error(String),
+}
+
+/// One hand at a seat as the players see it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HandState {
+    pub cards: Vec<Card>,
+    pub bet: u32,
+    pub outcome: Option<Outcome>,
+}
+
+/// One seat as the players see it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeatState {
+    /// Empty for a seat nobody is sitting in
+    pub name: String,
+    pub bankroll: u32,
+    pub bet: u32,
+    pub insurance: u32,
+    pub hands: Vec<HandState>,
+    /// Chips won back this round (stakes and insurance included), once the round is settled
+    pub payout: u32,
+}
+
+/// Everything on the table, the server sends it to each player after every change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableState {
+    pub phase: Phase,
+    pub seats: Vec<SeatState>,
+    /// The dealer's cards, without the hole card while it is face down
+    pub dealer: Vec<Card>,
+    pub hole_card_hidden: bool,
+    pub active_seat: Option<usize>,
+    pub active_hand: Option<usize>,
+    /// The seat of the player this copy was sent to, None while they wait for a seat
+    pub you: Option<usize>,
+    /// What the active hand is allowed to do
+    pub can_hit: bool,
+    pub can_double: bool,
+    pub can_split: bool,
+    pub can_surrender: bool,
+    pub max_insurance: u32,
+    pub can_take_even_money: bool,
+    pub cards_remaining: usize,
+    pub rules: String,
+    /// Smallest bet the table takes, a seat with fewer chips than this can rebuy
+    pub min_bet: u32,
+}
+
+impl TableState {
+    /// The table from the game, `names` has the player in each seat (None for an empty seat)
+    pub fn from_game(game: &BlackjackGame, names: &[Option<String>], you: Option<usize>) -> Self {
+        let seats = game
+            .seats()
+            .iter()
+            .enumerate()
+            .map(|(seat, player)| SeatState {
+                name: names.get(seat).cloned().flatten().unwrap_or_default(),
+                bankroll: player.bankroll(),
+                bet: player.bet(),
+                insurance: player.insurance(),
+                hands: player
+                    .hands()
+                    .iter()
+                    .map(|hand| HandState {
+                        cards: hand.hand.cards().to_vec(),
+                        bet: hand.bet,
+                        outcome: hand.outcome,
+                    })
+                    .collect(),
+                payout: player.total_payout(),
+            })
+            .collect();
+        let mut dealer = game.dealer().cards().to_vec();
+        if game.hole_card_hidden() {
+            dealer.truncate(1);
+        }
+        Self {
+            phase: game.phase(),
+            seats,
+            dealer,
+            hole_card_hidden: game.hole_card_hidden(),
+            active_seat: game.active_seat(),
+            active_hand: game.active_hand(),
+            you,
+            can_hit: game.can_hit(),
+            can_double: game.can_double(),
+            can_split: game.can_split(),
+            can_surrender: game.can_surrender(),
+            max_insurance: game.max_insurance(),
+            can_take_even_money: game.can_take_even_money(),
+            cards_remaining: game.shoe().cards_remaining(),
+            rules: game.rules().to_string(),
+            min_bet: game.rules().limits.min_bet,
+        }
+    }
+
+    /// True when it is the receiving player's turn to act
+    pub fn your_turn(&self) -> bool {
+        self.you.is_some() && self.active_seat == self.you
+    }
+
+    /// True when the receiving player is out of chips while bets are taken, the server allows a rebuy then
+    pub fn can_rebuy(&self) -> bool {
+        self.phase == Phase::Betting
+            && self
+                .you
+                .is_some_and(|seat| self.seats[seat].bankroll + self.seats[seat].bet < self.min_bet)
+    }
+}
+
+/// Sends one message with its length in front
+pub fn write_message<W: Write, T: Serialize>(writer: &mut W, message: &T) -> io::Result<()> {
+    let body = serde_json::to_vec(message).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    if body.len() > MAX_MESSAGE_LEN {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "message too long"));
+    }
+    writer.write_all(&(body.len() as u32).to_be_bytes())?;
+    writer.write_all(&body)?;
+    writer.flush()
+}
+
+/// Waits for one whole message, an error when the connection closes or sends something that is not a message
+pub fn read_message<R: Read, T: DeserializeOwned>(reader: &mut R) -> io::Result<T> {
+    let mut length = [0u8; 4];
+    reader.read_exact(&mut length)?;
+    let length = u32::from_be_bytes(length) as usize;
+    if length > MAX_MESSAGE_LEN {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "message too long"));
+    }
+    let mut body = vec![0u8; length];
+    reader.read_exact(&mut body)?;
+    serde_json::from_slice(&body).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn messages_read_back_what_was_written() {
+        let mut wire = Vec::new();
+        write_message(&mut wire, &ClientMessage::Join { name: "Sam".to_string() }).unwrap();
+        write_message(&mut wire, &ClientMessage::Bet { amount: 25 }).unwrap();
+        write_message(&mut wire, &ServerMessage::Error("It is not your turn".to_string())).unwrap();
+
+        let mut reader = Cursor::new(wire);
+        assert_eq!(
+            read_message::<_, ClientMessage>(&mut reader).unwrap(),
+            ClientMessage::Join { name: "Sam".to_string() }
+        );
+        assert_eq!(read_message::<_, ClientMessage>(&mut reader).unwrap(), ClientMessage::Bet { amount: 25 });
+        assert_eq!(
+            read_message::<_, ServerMessage>(&mut reader).unwrap(),
+            ServerMessage::Error("It is not your turn".to_string())
+        );
+        // Nothing left, the same as a closed connection
+        assert_eq!(
+            read_message::<_, ClientMessage>(&mut reader).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn the_whole_table_goes_over_the_wire() {
+        let mut game = BlackjackGame::new(Default::default(), 1000, 1);
+        game.place_bet(25).unwrap();
+        game.deal().unwrap();
+        let state = TableState::from_game(&game, &[Some("Sam".to_string())], Some(0));
+        assert_eq!(state.hole_card_hidden, state.dealer.len() == 1);
+
+        let mut wire = Vec::new();
+        write_message(&mut wire, &ServerMessage::Table(state.clone())).unwrap();
+        assert_eq!(
+            read_message::<_, ServerMessage>(&mut Cursor::new(wire)).unwrap(),
+            ServerMessage::Table(state)
+        );
+    }
+
+    #[test]
+    fn a_cut_off_message_is_an_error() {
+        // Only part of the length
+        let mut reader = Cursor::new(vec![0u8, 0]);
+        assert_eq!(
+            read_message::<_, ClientMessage>(&mut reader).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+
+        // The length promises more than comes
+        let mut wire = Vec::new();
+        write_message(&mut wire, &ClientMessage::Hit).unwrap();
+        wire.pop();
+        assert_eq!(
+            read_message::<_, ClientMessage>(&mut Cursor::new(wire)).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn a_message_that_is_too_long_is_refused() {
+        let mut wire = ((MAX_MESSAGE_LEN + 1) as u32).to_be_bytes().to_vec();
+        wire.extend(vec![b' '; MAX_MESSAGE_LEN + 1]);
+        assert_eq!(
+            read_message::<_, ClientMessage>(&mut Cursor::new(wire)).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        let mut wire = Vec::new();
+        let name = "x".repeat(MAX_MESSAGE_LEN);
+        assert_eq!(
+            write_message(&mut wire, &ClientMessage::Join { name }).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert!(wire.is_empty());
+    }
+
+    #[test]
+    fn garbage_is_not_a_message() {
+        let mut wire = 5u32.to_be_bytes().to_vec();
+        wire.extend(b"hello");
+        assert_eq!(
+            read_message::<_, ClientMessage>(&mut Cursor::new(wire)).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
@@ -0,0 +1,459 @@
+/*
+Program Details: Exact dealer odds and player expected values
+
+Works out, card by card from what is left in the shoe (no simulation):
+    - how likely the dealer is to finish on 17, 18, 19, 20, 21 or bust
+    - the player's expected value for standing, hitting, doubling and splitting
+
+In your game/mod.rs file add the following:
+    pub mod odds;
+
+Then with the other use commands add:
+use blackjack3::game::odds::{dealer_odds, player_ev, Composition};
+
+Usage examples:
+    // Straight from a game in progress, None outside the player's turn
+    if let Some((dealer, ev)) = odds_for(&game) {
+        println!("Dealer busts {:.1}%, best play {:?}", dealer.bust * 100.0, ev.best());
+    }
+
+    // From any set of unseen cards, for analysis scripts
+    let mut unseen = Composition::full(6);
+    unseen.remove(Rank::Ten);                           // player's cards
+    unseen.remove(Rank::Six);
+    unseen.remove(Rank::Nine);                          // dealer's up card
+    let dealer = dealer_odds(&unseen, Rank::Nine, &rules, true);
+    let ev = player_ev(&hand, Rank::Nine, &unseen, &rules, true);
+
+The composition passed in is every card the player has not seen, so the
+dealer's face down card belongs in it. "peeked" means the dealer has already
+checked for blackjack (and did not have it), which only changes the odds with
+an ace or ten showing.
+
+Expected values are in units of the original bet: 0.1 means winning a tenth
+of the bet on average. Hitting assumes the best play (hit or stand) after
+every card. Splitting is worked out as one split (no resplits) with the two
+hands drawn from the same unseen cards, the same way most strategy tables do it.
+*/
+use std::collections::HashMap;
+
+use crate::game::card::Rank;
+use crate::game::engine::{BlackjackGame, Phase};
+use crate::game::hand::Hand;
+use crate::game::rules::TableRules;
+use crate::game::shoe::Shoe;
+
+/// How many cards of each value are left. Index 0 is aces, 1 to 8 are twos to nines, 9 is every ten and face card.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Composition {
+    counts: [u32; 10],
+}
+
+impl Composition {
+    /// Every card in this many decks
+    pub fn full(decks: usize) -> Self {
+        let per_value = 4 * decks as u32;
+        let mut counts = [per_value; 10];
+        counts[9] = 4 * per_value;
+        Self { counts }
+    }
+
+    /// The cards still in the shoe
+    pub fn from_shoe(shoe: &Shoe) -> Self {
+        Self {
+            counts: shoe.remaining_by_value(),
+        }
+    }
+
+    pub fn count(&self, value: u8) -> u32 {
+        self.counts[value as usize - 1]
+    }
+
+    pub fn total(&self) -> u32 {
+        self.counts.iter().sum()
+    }
+
+    /// Puts a card back, like a face down card the player has not seen
+    pub fn add(&mut self, rank: Rank) {
+        self.counts[rank.value() as usize - 1] += 1;
+    }
+
+    /// Takes out a card that has been seen, does nothing if there are none left
+    pub fn remove(&mut self, rank: Rank) {
+        let count = &mut self.counts[rank.value() as usize - 1];
+        *count = count.saturating_sub(1);
+    }
+
+    fn take(&mut self, value: u8) {
+        self.counts[value as usize - 1] -= 1;
+    }
+
+    fn put_back(&mut self, value: u8) {
+        self.counts[value as usize - 1] += 1;
+    }
+}
+
+/// Chances of each way the dealer's hand can finish
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DealerOdds {
+    /// Chance of finishing on 17, 18, 19, 20 and 21 (not counting blackjack)
+    pub totals: [f64; 5],
+    pub bust: f64,
+    /// Always 0 once the dealer has peeked
+    pub blackjack: f64,
+}
+
+impl DealerOdds {
+    /// Chance of finishing on this total, 17 to 21
+    pub fn total(&self, total: u8) -> f64 {
+        match total {
+            17..=21 => self.totals[total as usize - 17],
+            _ => 0.0,
+        }
+    }
+}
+
+/// Expected value of each play for the player's hand, None when the play is not possible
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlayerEv {
+    pub stand: f64,
+    pub hit: f64,
+    pub double: Option<f64>,
+    pub split: Option<f64>,
+}
+
+impl PlayerEv {
+    /// The play worth the most and its value, as a name for showing
+    pub fn best(&self) -> (&'static str, f64) {
+        let mut best = ("Stand", self.stand);
+        let others = [("Hit", Some(self.hit)), ("Double", self.double), ("Split", self.split)];
+        for (name, ev) in others {
+            if let Some(ev) = ev {
+                if ev > best.1 {
+                    best = (name, ev);
+                }
+            }
+        }
+        best
+    }
+}
+
+/// Dealer and player odds for the active seat's hand, using the cards still in the shoe
+/// plus the dealer's face down card. None outside the players' turn.
+pub fn odds_for(game: &BlackjackGame) -> Option<(DealerOdds, PlayerEv)> {
+    if game.phase() != Phase::PlayerTurn {
+        return None;
+    }
+    let seat = game.active_seat()?;
+    let active = game.active_hand()?;
+    let up = game.dealer().cards().first()?.rank;
+    let mut unseen = Composition::from_shoe(game.shoe());
+    if game.hole_card_hidden() {
+        unseen.add(game.dealer().cards()[1].rank);
+    }
+    // The engine always peeks with an ace or ten showing
+    let peeked = up.is_ace() || up.value() == 10;
+    let dealer = dealer_odds(&unseen, up, game.rules(), peeked);
+
+    let player = &game.seats()[seat].hands()[active];
+    let mut ev = player_ev(&player.hand, up, &unseen, game.rules(), peeked);
+    // The engine decides these from the bankroll and split rules, so trust it over the cards alone
+    if !game.can_double() {
+        ev.double = None;
+    }
+    if !game.can_split() {
+        ev.split = None;
+    }
+    if !game.can_hit() {
+        ev.hit = ev.stand;
+    }
+    Some((dealer, ev))
+}
+
+/// Exact chances of the dealer's final hand from the up card and the unseen cards
+pub fn dealer_odds(unseen: &Composition, up: Rank, rules: &TableRules, peeked: bool) -> DealerOdds {
+    let mut unseen = *unseen;
+    let mut odds = DealerOdds::default();
+    let up = up.value();
+    dealer_draw(&mut unseen, up, up == 1, 1, 1.0, up, rules, peeked, &mut odds);
+    odds
+}
+
+// Deals the dealer every possible next card, weighted by how likely it is, until the hand stands or busts
+#[allow(clippy::too_many_arguments)]
+fn dealer_draw(
+    unseen: &mut Composition,
+    hard: u8,
+    has_ace: bool,
+    cards: u8,
+    chance: f64,
+    up: u8,
+    rules: &TableRules,
+    peeked: bool,
+    odds: &mut DealerOdds,
+) {
+    let soft = has_ace && hard + 10 <= 21;
+    let total = if soft { hard + 10 } else { hard };
+
+    if cards == 2 && total == 21 {
+        odds.blackjack += chance;
+        return;
+    }
+    if total > 21 {
+        odds.bust += chance;
+        return;
+    }
+    let hits = total < 17 || (total == 17 && soft && rules.dealer_hits_soft_17);
+    if cards >= 2 && !hits {
+        odds.totals[total as usize - 17] += chance;
+        return;
+    }
+
+    // After a peek the hole card cannot be the one that makes blackjack
+    let blocked = match (cards, peeked, up) {
+        (1, true, 1) => Some(10),
+        (1, true, 10) => Some(1),
+        _ => None,
+    };
+    let mut left = unseen.total();
+    if let Some(value) = blocked {
+        left -= unseen.count(value);
+    }
+    if left == 0 {
+        return;
+    }
+
+    for value in 1..=10u8 {
+        let count = unseen.count(value);
+        if count == 0 || Some(value) == blocked {
+            continue;
+        }
+        let next = chance * count as f64 / left as f64;
+        unseen.take(value);
+        dealer_draw(unseen, hard + value, has_ace || value == 1, cards + 1, next, up, rules, peeked, odds);
+        unseen.put_back(value);
+    }
+}
+
+/// Expected value of standing, hitting, doubling and splitting a hand against the up card
+pub fn player_ev(hand: &Hand, up: Rank, unseen: &Composition, rules: &TableRules, peeked: bool) -> PlayerEv {
+    let mut calc = Calculator {
+        rules,
+        up,
+        peeked,
+        dealer: HashMap::new(),
+        hits: HashMap::new(),
+    };
+    let mut unseen = *unseen;
+    let hard = hand.hard_total();
+    let has_ace = hand.cards().iter().any(|card| card.rank.is_ace());
+
+    let stand = if hand.is_blackjack() {
+        let dealer = calc.dealer(&unseen);
+        let win = rules.blackjack_payout.winnings(1000) as f64 / 1000.0;
+        win * (1.0 - dealer.blackjack)
+    } else {
+        calc.stand(&unseen, hand.best_total())
+    };
+    let hit = calc.hit(&mut unseen, hard, has_ace);
+    let double = if hand.len() == 2 {
+        Some(calc.double(&mut unseen, hard, has_ace))
+    } else {
+        None
+    };
+
+    let cards = hand.cards();
+    let split = if hand.len() == 2 && cards[0].rank.value() == cards[1].rank.value() {
+        Some(calc.split(&mut unseen, cards[0].rank.value()))
+    } else {
+        None
+    };
+
+    PlayerEv { stand, hit, double, split }
+}
+
+// Keeps the dealer odds and hitting values already worked out, hitting
+// reaches the same cards in different orders a lot
+struct Calculator<'a> {
+    rules: &'a TableRules,
+    up: Rank,
+    peeked: bool,
+    dealer: HashMap<Composition, DealerOdds>,
+    hits: HashMap<(Composition, u8, bool), f64>,
+}
+
+impl Calculator<'_> {
+    fn dealer(&mut self, unseen: &Composition) -> DealerOdds {
+        if let Some(odds) = self.dealer.get(unseen) {
+            return *odds;
+        }
+        let odds = dealer_odds(unseen, self.up, self.rules, self.peeked);
+        self.dealer.insert(*unseen, odds);
+        odds
+    }
+
+    // Standing on a total that is not a natural
+    fn stand(&mut self, unseen: &Composition, total: u8) -> f64 {
+        if total > 21 {
+            return -1.0;
+        }
+        let dealer = self.dealer(unseen);
+        let mut ev = dealer.bust - dealer.blackjack;
+        for dealer_total in 17..=21u8 {
+            let chance = dealer.total(dealer_total);
+            if total > dealer_total {
+                ev += chance;
+            } else if total < dealer_total {
+                ev -= chance;
+            }
+        }
+        ev
+    }
+
+    // Taking one card, then the better of hitting again or standing
+    fn hit(&mut self, unseen: &mut Composition, hard: u8, has_ace: bool) -> f64 {
+        if let Some(ev) = self.hits.get(&(*unseen, hard, has_ace)) {
+            return *ev;
+        }
+        let left = unseen.total();
+        if left == 0 {
+            return self.stand(unseen, best(hard, has_ace));
+        }
+        let mut ev = 0.0;
+        for value in 1..=10u8 {
+            let count = unseen.count(value);
+            if count == 0 {
+                continue;
+            }
+            let chance = count as f64 / left as f64;
+            let hard = hard + value;
+            let has_ace = has_ace || value == 1;
+            unseen.take(value);
+            let value_after = if hard > 21 {
+                -1.0
+            } else {
+                let stand = self.stand(unseen, best(hard, has_ace));
+                let hit = self.hit(unseen, hard, has_ace);
+                stand.max(hit)
+            };
+            unseen.put_back(value);
+            ev += chance * value_after;
+        }
+        self.hits.insert((*unseen, hard, has_ace), ev);
+        ev
+    }
+
+    // One card for twice the bet
+    fn double(&mut self, unseen: &mut Composition, hard: u8, has_ace: bool) -> f64 {
+        let left = unseen.total();
+        if left == 0 {
+            return 2.0 * self.stand(unseen, best(hard, has_ace));
+        }
+        let mut ev = 0.0;
+        for value in 1..=10u8 {
+            let count = unseen.count(value);
+            if count == 0 {
+                continue;
+            }
+            let chance = count as f64 / left as f64;
+            unseen.take(value);
+            ev += chance * 2.0 * self.stand(unseen, best(hard + value, has_ace || value == 1));
+            unseen.put_back(value);
+        }
+        ev
+    }
+
+    // Two hands that each start with one card of the pair
+    fn split(&mut self, unseen: &mut Composition, pair: u8) -> f64 {
+        let left = unseen.total();
+        if left == 0 {
+            return 0.0;
+        }
+        let mut ev = 0.0;
+        for value in 1..=10u8 {
+            let count = unseen.count(value);
+            if count == 0 {
+                continue;
+            }
+            let chance = count as f64 / left as f64;
+            let hard = pair + value;
+            let has_ace = pair == 1 || value == 1;
+            unseen.take(value);
+            // Split aces get one card each, a 21 after a split is not a blackjack
+            let stand = self.stand(unseen, best(hard, has_ace));
+            let hand_ev = if pair == 1 {
+                stand
+            } else {
+                let mut ev = stand.max(self.hit(unseen, hard, has_ace));
+                if self.rules.double_after_split {
+                    ev = ev.max(self.double(unseen, hard, has_ace));
+                }
+                ev
+            };
+            unseen.put_back(value);
+            ev += chance * hand_ev;
+        }
+        2.0 * ev
+    }
+}
+
+// Total the hand plays as
+fn best(hard: u8, has_ace: bool) -> u8 {
+    if has_ace && hard + 10 <= 21 {
+        hard + 10
+    } else {
+        hard
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::card::Card;
+
+    fn hand(text: &str) -> Hand {
+        let cards: Vec<Card> = text.split_whitespace().map(|card| card.parse().unwrap()).collect();
+        Hand::from_cards(&cards)
+    }
+
+    // Six decks less the player's cards and the dealer's up card
+    fn unseen(seen: &[Rank]) -> Composition {
+        let mut unseen = Composition::full(6);
+        for rank in seen {
+            unseen.remove(*rank);
+        }
+        unseen
+    }
+
+    #[test]
+    fn sixteen_against_a_ten() {
+        let unseen = unseen(&[Rank::Ten, Rank::Six, Rank::Ten]);
+        let ev = player_ev(&hand("10S 6H"), Rank::Ten, &unseen, &TableRules::default(), true);
+        assert!((ev.stand - -0.541).abs() < 0.0005, "stand {}", ev.stand);
+        assert!((ev.hit - -0.535).abs() < 0.0005, "hit {}", ev.hit);
+        assert_eq!(ev.best().0, "Hit");
+        assert_eq!(ev.split, None);
+    }
+
+    #[test]
+    fn dealer_odds_add_up_to_one() {
+        let rules = TableRules::default();
+        for up in [Rank::Ace, Rank::Six, Rank::Ten] {
+            for peeked in [true, false] {
+                let odds = dealer_odds(&unseen(&[up]), up, &rules, peeked);
+                let sum = odds.totals.iter().sum::<f64>() + odds.bust + odds.blackjack;
+                assert!((sum - 1.0).abs() < 1e-9, "{:?} peeked {} adds up to {}", up, peeked, sum);
+                if peeked {
+                    assert_eq!(odds.blackjack, 0.0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn a_peeked_natural_is_paid_in_full() {
+        let unseen = unseen(&[Rank::Ace, Rank::King, Rank::Ten]);
+        let ev = player_ev(&hand("AS KH"), Rank::Ten, &unseen, &TableRules::default(), true);
+        assert_eq!(ev.stand, 1.5);
+    }
+}
@@ -0,0 +1,411 @@
+/*
+Program Details: Player profiles saved between games
+
+A profile keeps a player's bankroll, their lifetime stats and the table they
+like to play at. Profiles are saved as JSON files, one per player, in the
+user's data folder:
+    Linux:   ~/.local/share/blackjack3/profiles
+    Windows: C:\Users\<name>\AppData\Roaming\blackjack3\profiles
+    macOS:   ~/Library/Application Support/blackjack3/profiles
+
+In your game/mod.rs file add the following:
+    pub mod profile;
+
+Then with the other use commands add:
+use blackjack3::game::profile::{Profile, ProfileStore};
+
+Usage examples:
+    let store = ProfileStore::open_default();
+    let profiles = store.list();             // every saved profile, sorted by name
+
+    let mut profile = Profile::new("Sam");
+    store.save(&profile)?;
+
+    // After a round is settled, from the seat the player sat in
+    profile.record_round(&game, seat);
+    store.save(&profile)?;
+
+    let profile = store.load("sam")?;        // names are not case sensitive
+    store.delete("Sam")?;
+
+Files from older versions load fine, anything missing gets its default value.
+*/
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::ai::SeatPlayer;
+use crate::game::counting::SYSTEM_NAMES;
+use crate::game::engine::{BlackjackGame, Outcome};
+use crate::game::rules::TableRules;
+
+/// Chips a new player sits down with
+pub const STARTING_CHIPS: u32 = 500;
+
+/// Longest name a profile can have
+pub const MAX_NAME_LEN: usize = 20;
+
+/// Totals over every round a profile has played
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LifetimeStats {
+    pub rounds_played: u32,
+    /// Counts each split hand on its own
+    pub hands_played: u32,
+    pub hands_won: u32,
+    pub hands_pushed: u32,
+    pub hands_lost: u32,
+    pub blackjacks: u32,
+    pub surrenders: u32,
+    /// Chips bet, insurance included
+    pub total_wagered: u64,
+    /// Chips paid back, stakes included
+    pub total_returned: u64,
+    /// Best net win in a single round
+    pub biggest_win: u32,
+    pub peak_bankroll: u32,
+    /// Times the bankroll was topped back up after running out
+    pub rebuys: u32,
+}
+
+impl LifetimeStats {
+    /// Chips won (or lost when negative) over every round
+    pub fn net(&self) -> i64 {
+        self.total_returned as i64 - self.total_wagered as i64
+    }
+}
+
+/// Keys for the table's buttons, by name: a letter, a digit, Space, Enter, Tab or Backspace.
+/// A name that is not a key leaves that button to the mouse
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyBindings {
+    /// (default: H)
+    pub hit: String,
+    /// (default: S)
+    pub stand: String,
+    /// (default: D)
+    pub double: String,
+    /// (default: P)
+    pub split: String,
+    /// (default: R)
+    pub surrender: String,
+    /// Deals with the bet in the bet box, and starts the next round once one is settled (default: Space)
+    pub deal: String,
+    /// Puts the last bet back in the bet box (default: B)
+    pub rebet: String,
+    /// Deals with the bet typed in the bet box, or answers the count quiz.
+    /// The only key that works while typing in a box (default: Enter)
+    pub confirm: String,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            hit: "H".to_string(),
+            stand: "S".to_string(),
+            double: "D".to_string(),
+            split: "P".to_string(),
+            surrender: "R".to_string(),
+            deal: "Space".to_string(),
+            rebet: "B".to_string(),
+            confirm: "Enter".to_string(),
+        }
+    }
+}
+
+/// Table the player wants to sit at and the bet they used last
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TablePreferences {
+    pub rules: TableRules,
+    /// Filled into the bet box when the profile is loaded, 0 for none
+    pub last_bet: u32,
+    /// Show the basic strategy play for the hand being played
+    pub show_hints: bool,
+    /// Point out plays that go against basic strategy
+    pub check_plays: bool,
+    /// Show the dealer's chances and the value of each play
+    pub show_odds: bool,
+    /// Quiz the player on the running count now and then
+    pub count_trainer: bool,
+    /// Name of the counting system the trainer uses, see counting::SYSTEM_NAMES (default: Hi-Lo)
+    pub counting_system: String,
+    /// Who sits at the table from left to right, exactly one of them is the player (default: just the player)
+    pub seats: Vec<SeatPlayer>,
+    /// Seconds a card takes to slide out of the shoe or turn over, 0 for no animation (default: 0.3)
+    pub deal_speed: f32,
+    /// Seconds the dealer waits before turning the hole card over and before each card they draw (default: 0.8)
+    pub dealer_delay: f32,
+    pub keys: KeyBindings,
+}
+
+impl Default for TablePreferences {
+    fn default() -> Self {
+        Self {
+            rules: TableRules::default(),
+            last_bet: 0,
+            show_hints: false,
+            check_plays: false,
+            show_odds: false,
+            count_trainer: false,
+            counting_system: SYSTEM_NAMES[0].to_string(),
+            seats: vec![SeatPlayer::Human],
+            deal_speed: 0.3,
+            dealer_delay: 0.8,
+            keys: KeyBindings::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub name: String,
+    pub bankroll: u32,
+    pub stats: LifetimeStats,
+    pub preferences: TablePreferences,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self::new("Player")
+    }
+}
+
+impl Profile {
+    /// A new player with the starting chips
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.trim().to_string(),
+            bankroll: STARTING_CHIPS,
+            stats: LifetimeStats {
+                peak_bankroll: STARTING_CHIPS,
+                ..Default::default()
+            },
+            preferences: TablePreferences::default(),
+        }
+    }
+
+    /// Adds a settled round to the stats and takes the bankroll from the player's seat
+    pub fn record_round(&mut self, game: &BlackjackGame, seat: usize) {
+        let Some(player) = game.seats().get(seat) else {
+            return;
+        };
+        self.bankroll = player.bankroll();
+        self.stats.peak_bankroll = self.stats.peak_bankroll.max(self.bankroll);
+        // Sitting a round out does not count as playing it
+        if !player.is_playing() {
+            return;
+        }
+        let stats = &mut self.stats;
+        stats.rounds_played += 1;
+        for hand in player.hands() {
+            stats.hands_played += 1;
+            match hand.outcome {
+                Some(Outcome::Blackjack) => {
+                    stats.hands_won += 1;
+                    stats.blackjacks += 1;
+                }
+                Some(Outcome::Win) => stats.hands_won += 1,
+                Some(Outcome::Push) => stats.hands_pushed += 1,
+                Some(Outcome::Lose) | Some(Outcome::Bust) => stats.hands_lost += 1,
+                Some(Outcome::Surrender) => stats.surrenders += 1,
+                None => {}
+            }
+        }
+
+        let staked = player.total_staked();
+        let returned = player.total_payout();
+        stats.total_wagered += staked as u64;
+        stats.total_returned += returned as u64;
+        stats.biggest_win = stats.biggest_win.max(returned.saturating_sub(staked));
+    }
+
+    /// Tops the bankroll back up to the starting chips after going broke
+    pub fn rebuy(&mut self) {
+        self.bankroll = STARTING_CHIPS;
+        self.stats.rebuys += 1;
+    }
+}
+
+#[derive(Debug)]
+pub enum ProfileError {
+    /// Names have to be 1 to 20 letters, numbers, spaces or '-'
+    BadName,
+    /// A profile with that name is already saved
+    AlreadyExists,
+    Io(io::Error),
+    Parse(serde_json::Error),
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileError::BadName => write!(f, "Names are 1-{} letters or numbers", MAX_NAME_LEN),
+            ProfileError::AlreadyExists => write!(f, "That name is taken"),
+            ProfileError::Io(err) => write!(f, "Could not save: {}", err),
+            ProfileError::Parse(err) => write!(f, "Save file is damaged: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ProfileError {}
+
+impl From<io::Error> for ProfileError {
+    fn from(err: io::Error) -> Self {
+        ProfileError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for ProfileError {
+    fn from(err: serde_json::Error) -> Self {
+        ProfileError::Parse(err)
+    }
+}
+
+/// True for names that can be used as a profile (and its file name).
+/// No '_', the file name has one for each space
+pub fn valid_name(name: &str) -> bool {
+    let name = name.trim();
+    !name.is_empty() && name.chars().count() <= MAX_NAME_LEN && name.chars().all(|c| c.is_alphanumeric() || c == ' ' || c == '-')
+}
+
+/// The folder profiles are saved in
+#[derive(Debug, Clone)]
+pub struct ProfileStore {
+    dir: PathBuf,
+}
+
+impl ProfileStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Profiles folder inside the user's data folder, or next to the game if there is none
+    pub fn open_default() -> Self {
+        let base = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+        Self::new(base.join("blackjack3").join("profiles"))
+    }
+
+    // Names are case-insensitive on some systems, so files always use lower case
+    fn path_for(&self, name: &str) -> PathBuf {
+        let file = name.trim().to_lowercase().replace(' ', "_");
+        self.dir.join(format!("{}.json", file))
+    }
+
+    pub fn exists(&self, name: &str) -> bool {
+        valid_name(name) && self.path_for(name).is_file()
+    }
+
+    /// Every profile that could be read, sorted by name
+    pub fn list(&self) -> Vec<Profile> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut profiles: Vec<Profile> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| fs::read_to_string(path).ok())
+            .filter_map(|text| serde_json::from_str(&text).ok())
+            .collect();
+        profiles.sort_by_key(|profile: &Profile| profile.name.to_lowercase());
+        profiles
+    }
+
+    pub fn load(&self, name: &str) -> Result<Profile, ProfileError> {
+        if !valid_name(name) {
+            return Err(ProfileError::BadName);
+        }
+        let text = fs::read_to_string(self.path_for(name))?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    /// Makes and saves a new profile, the name must not be taken
+    pub fn create(&self, name: &str) -> Result<Profile, ProfileError> {
+        if !valid_name(name) {
+            return Err(ProfileError::BadName);
+        }
+        if self.exists(name) {
+            return Err(ProfileError::AlreadyExists);
+        }
+        let profile = Profile::new(name);
+        self.save(&profile)?;
+        Ok(profile)
+    }
+
+    /// Writes the profile to a temporary file first so a crash never leaves half a save behind
+    pub fn save(&self, profile: &Profile) -> Result<(), ProfileError> {
+        if !valid_name(&profile.name) {
+            return Err(ProfileError::BadName);
+        }
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path_for(&profile.name);
+        let temp = path.with_extension("json.tmp");
+        fs::write(&temp, serde_json::to_string_pretty(profile)?)?;
+        fs::rename(&temp, &path)?;
+        Ok(())
+    }
+
+    pub fn delete(&self, name: &str) -> Result<(), ProfileError> {
+        if !valid_name(name) {
+            return Err(ProfileError::BadName);
+        }
+        fs::remove_file(self.path_for(name))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A store of its own in the temp folder for each test
+    fn temp_store(test: &str) -> ProfileStore {
+        let dir = std::env::temp_dir().join(format!("blackjack3-{}-{}", test, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        ProfileStore::new(dir)
+    }
+
+    #[test]
+    fn saved_profiles_load_back_and_delete() {
+        let store = temp_store("profiles");
+        let mut profile = store.create("Sam Smith").unwrap();
+        assert!(matches!(store.create("sam smith"), Err(ProfileError::AlreadyExists)));
+        profile.bankroll = 1234;
+        store.save(&profile).unwrap();
+
+        let loaded = store.load("SAM SMITH").unwrap();
+        assert_eq!(loaded.name, "Sam Smith");
+        assert_eq!(loaded.bankroll, 1234);
+        assert_eq!(store.list().len(), 1);
+
+        store.delete("Sam Smith").unwrap();
+        assert!(!store.exists("Sam Smith"));
+        assert!(store.load("Sam Smith").is_err());
+        let _ = fs::remove_dir_all(&store.dir);
+    }
+
+    #[test]
+    fn bad_names_are_refused() {
+        let store = temp_store("names");
+        assert!(matches!(store.load("../secret"), Err(ProfileError::BadName)));
+        assert!(matches!(store.create("   "), Err(ProfileError::BadName)));
+        assert!(matches!(store.delete("../secret"), Err(ProfileError::BadName)));
+        assert!(!store.exists("../secret"));
+    }
+
+    #[test]
+    fn names_never_share_a_file() {
+        let store = temp_store("underscore");
+        store.create("Sam Smith").unwrap();
+        // Sam_Smith would be saved over Sam Smith
+        assert!(matches!(store.create("Sam_Smith"), Err(ProfileError::BadName)));
+        assert!(valid_name("Jo-Ann 2"));
+        let _ = fs::remove_dir_all(&store.dir);
+    }
+}
@@ -0,0 +1,255 @@
+/*
+Program Details: Round records and a replay that deals them again
+
+Every round the engine plays is recorded: the shoe seed, which shuffle of the
+shoe it came from, how far into that shuffle it started, every card it took in
+order, what the player chose to do and all the events. That is enough to rebuild the shoe and deal the round
+again card by card, so a strange hand can be looked at again (and reproduced
+by starting the game with the same --seed).
+
+In your game/mod.rs file add the following:
+    pub mod replay;
+
+Then with the other use commands add:
+use blackjack3::game::replay::{Replay, RoundRecord};
+
+Usage examples:
+    // The round being played, or the last one between rounds
+    let record = game.round_record().unwrap().clone();
+    println!("seed {} shuffle {} card {}", record.seed, record.shuffle, record.position);
+
+    let mut replay = Replay::new(record);
+    while replay.step() {
+        // One more card is on the table
+        replay.dealer();
+        replay.seats();     // the hands of every seat, empty for seats that sat out
+    }
+    replay.faithful();   // false if the rebuilt shoe dealt a different card than the record
+*/
+use std::fmt;
+
+use crate::game::card::Card;
+use crate::game::engine::{DealtTo, GameEvent, Outcome};
+use crate::game::hand::Hand;
+use crate::game::shoe::Shoe;
+
+/// A decision the player made during a round
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Insurance { amount: u32 },
+    EvenMoney,
+    DeclineInsurance,
+    DeclineSurrender,
+    Hit,
+    Stand,
+    Double,
+    Split,
+    Surrender,
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Insurance { amount } => write!(f, "insurance {}", amount),
+            Action::EvenMoney => write!(f, "even money"),
+            Action::DeclineInsurance => write!(f, "no insurance"),
+            Action::DeclineSurrender => write!(f, "no surrender"),
+            Action::Hit => write!(f, "hit"),
+            Action::Stand => write!(f, "stand"),
+            Action::Double => write!(f, "double"),
+            Action::Split => write!(f, "split"),
+            Action::Surrender => write!(f, "surrender"),
+        }
+    }
+}
+
+/// Everything needed to deal a round again exactly as it was played
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundRecord {
+    /// Seed the shoe was built with
+    pub seed: u64,
+    /// Which shuffle of the shoe the round was dealt from, 0 for the first
+    pub shuffle: u64,
+    /// Cards already dealt from that shuffle when the round started
+    pub position: usize,
+    /// Cards left out of that shuffle because they were on the table, see Shoe::held
+    pub held: Vec<Card>,
+    pub decks: usize,
+    /// Bet of each seat at the table, 0 for a seat that sat the round out
+    pub bets: Vec<u32>,
+    /// Every card the round took from the shoe, in the order they came out
+    pub cards: Vec<Card>,
+    /// What the players chose to do and which seat did it, in order
+    pub actions: Vec<(usize, Action)>,
+    pub events: Vec<GameEvent>,
+}
+
+impl RoundRecord {
+    /// Starts a record for a round about to be dealt from this shoe
+    pub fn new(shoe: &Shoe, bets: Vec<u32>) -> Self {
+        Self {
+            seed: shoe.seed(),
+            shuffle: shoe.shuffle_number(),
+            position: shoe.cards_dealt(),
+            held: shoe.held().to_vec(),
+            decks: shoe.decks(),
+            bets,
+            cards: Vec::new(),
+            actions: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// The shoe as it was when the round started
+    pub fn rebuild_shoe(&self) -> Shoe {
+        Shoe::resume(self.decks, 1.0, self.seed, self.shuffle, self.position, &self.held)
+    }
+}
+
+/// One of a seat's hands while a round is being replayed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayHand {
+    pub hand: Hand,
+    pub bet: u32,
+    pub outcome: Option<Outcome>,
+}
+
+/// Deals a recorded round again from a rebuilt shoe, one card at a time
+#[derive(Debug, Clone)]
+pub struct Replay {
+    record: RoundRecord,
+    shoe: Shoe,
+    next: usize, // Index of the next event to play
+    dealt: usize,
+    dealer: Hand,
+    hole_card_hidden: bool,
+    seats: Vec<Vec<ReplayHand>>,
+    faithful: bool,
+}
+
+impl Replay {
+    pub fn new(record: RoundRecord) -> Self {
+        let shoe = record.rebuild_shoe();
+        let seats = vec![Vec::new(); record.bets.len()];
+        Self {
+            record,
+            shoe,
+            next: 0,
+            dealt: 0,
+            dealer: Hand::new(),
+            hole_card_hidden: false,
+            seats,
+            faithful: true,
+        }
+    }
+
+    pub fn record(&self) -> &RoundRecord {
+        &self.record
+    }
+
+    /// Back to the empty table before the first card
+    pub fn restart(&mut self) {
+        *self = Self::new(self.record.clone());
+    }
+
+    pub fn dealer(&self) -> &Hand {
+        &self.dealer
+    }
+
+    pub fn hole_card_hidden(&self) -> bool {
+        self.hole_card_hidden
+    }
+
+    /// Hands of each seat, seat by seat
+    pub fn seats(&self) -> &[Vec<ReplayHand>] {
+        &self.seats
+    }
+
+    /// Cards dealt so far in the replay
+    pub fn cards_dealt(&self) -> usize {
+        self.dealt
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.record.events.len()
+    }
+
+    /// True while every card the rebuilt shoe dealt matches the recorded one
+    pub fn faithful(&self) -> bool {
+        self.faithful
+    }
+
+    /// Plays the round on until one more card is dealt or turned over.
+    /// Returns false once there is nothing left to show.
+    pub fn step(&mut self) -> bool {
+        while let Some(event) = self.record.events.get(self.next).cloned() {
+            self.next += 1;
+            if self.apply(&event) {
+                return true;
+            }
+        }
+        false
+    }
+
+    // Takes the next card from the rebuilt shoe and checks it against the record
+    fn draw(&mut self) -> Card {
+        let card = self.shoe.deal();
+        if self.record.cards.get(self.dealt) != Some(&card) {
+            self.faithful = false;
+        }
+        self.dealt += 1;
+        card
+    }
+
+    // Changes the table for one event, true when a card was put down or turned over
+    fn apply(&mut self, event: &GameEvent) -> bool {
+        match *event {
+            GameEvent::BetPlaced { seat, amount } => {
+                self.seats[seat] = vec![ReplayHand {
+                    hand: Hand::new(),
+                    bet: amount,
+                    outcome: None,
+                }];
+            }
+            GameEvent::CardDealt { to, card } => {
+                let drawn = self.draw();
+                if drawn != card {
+                    self.faithful = false;
+                }
+                match to {
+                    DealtTo::Dealer => self.dealer.push(drawn),
+                    DealtTo::Player { seat, hand } => self.seats[seat][hand].hand.push(drawn),
+                }
+                return true;
+            }
+            GameEvent::HoleCardDealt => {
+                let hole = self.draw();
+                self.dealer.push(hole);
+                self.hole_card_hidden = true;
+                return true;
+            }
+            GameEvent::HoleCardRevealed(_) => {
+                self.hole_card_hidden = false;
+                return true;
+            }
+            GameEvent::Doubled { seat, hand } => self.seats[seat][hand].bet *= 2,
+            GameEvent::Split { seat, hand, new_hand } => {
+                let hands = &mut self.seats[seat];
+                let cards = hands[hand].hand.cards().to_vec();
+                let bet = hands[hand].bet;
+                hands[hand].hand = Hand::from_cards(&cards[..1]);
+                hands.insert(
+                    new_hand,
+                    ReplayHand {
+                        hand: Hand::from_cards(&cards[1..]),
+                        bet,
+                        outcome: None,
+                    },
+                );
+            }
+            GameEvent::Settled { seat, hand, outcome, .. } => self.seats[seat][hand].outcome = Some(outcome),
+            _ => {}
+        }
+        false
+    }
+}
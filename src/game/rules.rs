@@ -0,0 +1,136 @@
+/*
+Program Details: House rules for a blackjack table
+
+In your game/mod.rs file add the following:
+    pub mod rules;
+
+Then with the other use commands add:
+use blackjack3::game::rules::TableRules;
+
+Usage examples:
+    // Standard 6 deck game (see Default below)
+    let rules = TableRules::default();
+
+    // Change only the rules you care about
+    let rules = TableRules {
+        decks: 2,
+        dealer_hits_soft_17: false,
+        blackjack_payout: BlackjackPayout::SixToFive,
+        ..Default::default()
+    };
+
+    println!("{}", rules);   // "6 decks, H17, BJ pays 3:2, DAS, late surrender, bets 5-500"
+*/
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::limits::TableLimits;
+use crate::game::side_bets::SideBetPaytables;
+
+/// How much a natural blackjack pays
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BlackjackPayout {
+    ThreeToTwo,
+    SixToFive,
+    EvenMoney,
+}
+
+impl BlackjackPayout {
+    /// Winnings for a blackjack on this bet (the stake is not included), rounded down
+    pub fn winnings(self, bet: u32) -> u32 {
+        match self {
+            BlackjackPayout::ThreeToTwo => bet * 3 / 2,
+            BlackjackPayout::SixToFive => bet * 6 / 5,
+            BlackjackPayout::EvenMoney => bet,
+        }
+    }
+}
+
+impl fmt::Display for BlackjackPayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlackjackPayout::ThreeToTwo => write!(f, "3:2"),
+            BlackjackPayout::SixToFive => write!(f, "6:5"),
+            BlackjackPayout::EvenMoney => write!(f, "1:1"),
+        }
+    }
+}
+
+/// When a player may give up half their bet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Surrender {
+    /// Surrender is not offered
+    None,
+    /// Only counts if the dealer does not have blackjack, otherwise the whole bet is lost
+    Late,
+    /// Half the bet comes back even when the dealer has blackjack, taken before the dealer peeks
+    Early,
+}
+
+/// Rules the engine plays by, saved with a player's profile
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TableRules {
+    /// Dealer hits a soft 17 (H17) instead of standing on all 17s (S17) (default: true)
+    pub dealer_hits_soft_17: bool,
+    /// Payout for a natural blackjack (default: 3:2)
+    pub blackjack_payout: BlackjackPayout,
+    /// Number of decks in the shoe, 1 to 8 (default: 6)
+    pub decks: usize,
+    /// How much of the shoe is dealt before the cut card (default: 0.75)
+    pub penetration: f32,
+    /// Doubling is allowed on hands that came from a split (default: true)
+    pub double_after_split: bool,
+    /// Most hands a player can have after splitting and resplitting (default: 4)
+    pub max_hands: usize,
+    /// Split aces can be split again (default: false)
+    pub resplit_aces: bool,
+    /// Surrender option (default: Late)
+    pub surrender: Surrender,
+    /// What Perfect Pairs and 21+3 pay
+    pub side_bets: SideBetPaytables,
+    /// Smallest and biggest main bet (default: 5-500)
+    pub limits: TableLimits,
+}
+
+impl Default for TableRules {
+    fn default() -> Self {
+        Self {
+            dealer_hits_soft_17: true,
+            blackjack_payout: BlackjackPayout::ThreeToTwo,
+            decks: 6,
+            penetration: 0.75,
+            double_after_split: true,
+            max_hands: 4,
+            resplit_aces: false,
+            surrender: Surrender::Late,
+            side_bets: SideBetPaytables::default(),
+            limits: TableLimits::default(),
+        }
+    }
+}
+
+impl fmt::Display for TableRules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let decks = if self.decks == 1 {
+            "1 deck".to_string()
+        } else {
+            format!("{} decks", self.decks)
+        };
+        let dealer = if self.dealer_hits_soft_17 { "H17" } else { "S17" };
+        write!(f, "{}, {}, BJ pays {}", decks, dealer, self.blackjack_payout)?;
+        if self.double_after_split {
+            write!(f, ", DAS")?;
+        }
+        if self.resplit_aces {
+            write!(f, ", RSA")?;
+        }
+        match self.surrender {
+            Surrender::None => {}
+            Surrender::Late => write!(f, ", late surrender")?,
+            Surrender::Early => write!(f, ", early surrender")?,
+        }
+        write!(f, ", bets {}", self.limits)
+    }
+}
@@ -0,0 +1,388 @@
+/*
+Program Details: Table server for games on the local network
+
+The server owns the only real BlackjackGame. Players connect over TCP, send
+what they want to do (see the net module) and get the whole table back after
+every change, so every screen shows the same table.
+
+In your game/mod.rs file add the following:
+    pub mod server;
+
+Then with the other use commands add:
+use blackjack3::game::server::serve;
+
+Usage examples:
+    // Blocks for as long as the table is open
+    serve("0.0.0.0:7777", TableRules::default(), seed)?;
+
+    // Or drive a table without the network, each player has an id of your choosing
+    let mut table = Table::new(TableRules::default(), seed);
+    table.handle(1, ClientMessage::Join { name: "Sam".to_string() })?;
+    table.handle(1, ClientMessage::Bet { amount: 25 })?;
+    table.handle(1, ClientMessage::Deal)?;
+    let state = table.state_for(1);
+
+Seats:
+    Players join into an empty seat, or a new one on the right while the table
+    is betting. Someone who joins during a round waits for the next one if no
+    seat is free. A player who leaves takes their chips with them, a round they
+    were part of is played out for them by standing.
+*/
+use std::collections::HashMap;
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::Duration;
+
+use crate::game::engine::{BlackjackGame, GameError, Phase, MAX_SEATS};
+use crate::game::net::{read_message, write_message, ClientMessage, ServerMessage, TableState};
+use crate::game::profile::{valid_name, STARTING_CHIPS};
+use crate::game::rules::TableRules;
+
+/// Tells the players on the server apart
+pub type ClientId = u64;
+
+// Someone who joined the table
+struct Player {
+    id: ClientId,
+    name: String,
+}
+
+/// A table and who is sitting at it
+pub struct Table {
+    game: BlackjackGame,
+    players: Vec<Option<Player>>, // One for each seat of the game, None for an empty seat
+    waiting: Vec<Player>,         // Joined during a round with no seat free
+}
+
+impl Table {
+    pub fn new(rules: TableRules, seed: u64) -> Self {
+        Self {
+            game: BlackjackGame::new(rules, STARTING_CHIPS, seed),
+            players: vec![None],
+            waiting: Vec::new(),
+        }
+    }
+
+    pub fn game(&self) -> &BlackjackGame {
+        &self.game
+    }
+
+    /// The seat a player is sitting in, None when they are waiting or never joined
+    pub fn seat_of(&self, id: ClientId) -> Option<usize> {
+        self.players
+            .iter()
+            .position(|player| player.as_ref().is_some_and(|player| player.id == id))
+    }
+
+    /// The table as one player sees it
+    pub fn state_for(&self, id: ClientId) -> TableState {
+        let names: Vec<Option<String>> = self
+            .players
+            .iter()
+            .map(|player| player.as_ref().map(|player| player.name.clone()))
+            .collect();
+        TableState::from_game(&self.game, &names, self.seat_of(id))
+    }
+
+    /// Does what a player asked for, the error text is sent back to them
+    pub fn handle(&mut self, id: ClientId, message: ClientMessage) -> Result<(), String> {
+        match message {
+            ClientMessage::Join { name } => return self.join(id, &name),
+            ClientMessage::Leave => {
+                self.leave(id);
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        let seat = self.seat_of(id).ok_or_else(|| "Wait for a seat first".to_string())?;
+        let plays = !matches!(
+            message,
+            ClientMessage::Bet { .. } | ClientMessage::Deal | ClientMessage::NextRound | ClientMessage::Rebuy
+        );
+        if plays && self.game.active_seat() != Some(seat) {
+            return Err("It is not your turn".to_string());
+        }
+        let result = match message {
+            ClientMessage::Bet { amount } => self.game.place_bet_at(seat, amount).map(|_| ()),
+            ClientMessage::Deal => self.game.deal().map(|_| ()),
+            ClientMessage::Hit => self.game.hit().map(|_| ()),
+            ClientMessage::Stand => self.game.stand().map(|_| ()),
+            ClientMessage::Double => self.game.double().map(|_| ()),
+            ClientMessage::Split => self.game.split().map(|_| ()),
+            ClientMessage::Surrender => self.game.surrender().map(|_| ()),
+            ClientMessage::Insurance { amount } => self.game.take_insurance(amount).map(|_| ()),
+            ClientMessage::EvenMoney => self.game.take_even_money().map(|_| ()),
+            ClientMessage::DeclineInsurance => self.game.decline_insurance().map(|_| ()),
+            ClientMessage::DeclineSurrender => self.game.decline_surrender().map(|_| ()),
+            ClientMessage::NextRound => self.game.next_round().map(|_| ()),
+            ClientMessage::Rebuy => return self.rebuy(seat),
+            ClientMessage::Join { .. } | ClientMessage::Leave => Ok(()),
+        };
+        result.map_err(|err: GameError| err.to_string())?;
+        self.play_on();
+        Ok(())
+    }
+
+    fn join(&mut self, id: ClientId, name: &str) -> Result<(), String> {
+        if self.seat_of(id).is_some() || self.waiting.iter().any(|player| player.id == id) {
+            return Err("You already joined".to_string());
+        }
+        if !valid_name(name) {
+            return Err("Pick a name of letters and numbers".to_string());
+        }
+        let seated = self.players.iter().flatten().count();
+        if seated + self.waiting.len() >= MAX_SEATS {
+            return Err(GameError::TableFull.to_string());
+        }
+        self.waiting.push(Player {
+            id,
+            name: name.trim().to_string(),
+        });
+        self.seat_waiting();
+        Ok(())
+    }
+
+    /// Takes a player away from the table, nothing happens for somebody who never joined
+    pub fn leave(&mut self, id: ClientId) {
+        self.waiting.retain(|player| player.id != id);
+        let Some(seat) = self.seat_of(id) else {
+            return;
+        };
+        if self.game.phase() == Phase::Betting {
+            let _ = self.game.clear_bet(seat);
+        }
+        self.players[seat] = None;
+        self.play_on();
+        self.seat_waiting();
+    }
+
+    fn rebuy(&mut self, seat: usize) -> Result<(), String> {
+        if self.game.phase() != Phase::Betting {
+            return Err(GameError::WrongPhase(self.game.phase()).to_string());
+        }
+        let player = &self.game.seats()[seat];
+        if player.bankroll() + player.bet() >= self.game.rules().limits.min_bet {
+            return Err("You still have chips".to_string());
+        }
+        self.game.set_bankroll_at(seat, STARTING_CHIPS).map_err(|err| err.to_string())
+    }
+
+    // Empty seats are filled first, new seats can only be added between rounds.
+    // Empty seats on the right are taken away between rounds so the table stays together
+    fn seat_waiting(&mut self) {
+        while !self.waiting.is_empty() {
+            // A seat left empty during a round is only free once its hands are settled
+            let free = (0..self.players.len())
+                .find(|seat| self.players[*seat].is_none() && (!self.game.seats()[*seat].is_playing() || self.game.phase() == Phase::Settlement));
+            let seat = match free {
+                Some(seat) => seat,
+                None => match self.game.add_seat(STARTING_CHIPS) {
+                    Ok(seat) => {
+                        self.players.push(None);
+                        seat
+                    }
+                    Err(_) => break,
+                },
+            };
+            let _ = self.game.set_bankroll_at(seat, STARTING_CHIPS);
+            self.players[seat] = Some(self.waiting.remove(0));
+        }
+        if self.game.phase() == Phase::Betting {
+            while self.players.len() > 1 && self.players.last().is_some_and(|player| player.is_none()) {
+                let _ = self.game.remove_seat(self.players.len() - 1);
+                self.players.pop();
+            }
+        }
+    }
+
+    // Stands and declines insurance and surrender for empty seats and plays the dealer, until a player has to act
+    fn play_on(&mut self) {
+        loop {
+            let empty_turn = self.game.active_seat().is_some_and(|seat| self.players[seat].is_none());
+            let result = match self.game.phase() {
+                Phase::Dealing if empty_turn => self.game.decline_surrender(),
+                Phase::Insurance if empty_turn => self.game.decline_insurance(),
+                Phase::PlayerTurn if empty_turn => self.game.stand(),
+                Phase::DealerTurn => self.game.play_dealer(),
+                // Nobody left to start the next round
+                Phase::Settlement if self.players.iter().all(|player| player.is_none()) => self.game.next_round(),
+                _ => break,
+            };
+            if result.is_err() {
+                break;
+            }
+        }
+        if self.game.phase() == Phase::Betting {
+            self.seat_waiting();
+        }
+    }
+}
+
+// What the threads reading from the players pass to the table
+enum Incoming {
+    Connected(TcpStream),
+    Message(ClientMessage),
+    Disconnected,
+}
+
+/// Opens a table on `addr` (for example "0.0.0.0:7777") and runs it, only returns if the address cannot be used
+pub fn serve(addr: &str, rules: TableRules, seed: u64) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    println!("table open on {}", listener.local_addr()?);
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || accept(listener, sender));
+
+    // Only this thread touches the table, messages are handled in the order they arrive
+    let mut table = Table::new(rules, seed);
+    let mut writers: HashMap<ClientId, TcpStream> = HashMap::new();
+    for (id, incoming) in receiver {
+        match incoming {
+            Incoming::Connected(stream) => {
+                println!("player {} connected", id);
+                writers.insert(id, stream);
+            }
+            Incoming::Message(message) => {
+                if let Err(text) = table.handle(id, message) {
+                    if let Some(writer) = writers.get_mut(&id) {
+                        let _ = write_message(writer, &ServerMessage::Error(text));
+                    }
+                    continue;
+                }
+            }
+            Incoming::Disconnected => {
+                println!("player {} left", id);
+                writers.remove(&id);
+                table.leave(id);
+            }
+        }
+        // Everybody gets the table after every change, a player that cannot keep up is dropped
+        writers.retain(|id, writer| write_message(writer, &ServerMessage::Table(table.state_for(*id))).is_ok());
+    }
+    Ok(())
+}
+
+// Gives each new connection an id and a thread of its own to read from
+fn accept(listener: TcpListener, sender: Sender<(ClientId, Incoming)>) {
+    for (id, stream) in (1..).zip(listener.incoming()) {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let Ok(writer) = stream.try_clone() else {
+            continue;
+        };
+        let _ = writer.set_write_timeout(Some(Duration::from_secs(5)));
+        if sender.send((id, Incoming::Connected(writer))).is_err() {
+            return;
+        }
+        let sender = sender.clone();
+        thread::spawn(move || read_client(id, stream, sender));
+    }
+}
+
+fn read_client(id: ClientId, mut stream: TcpStream, sender: Sender<(ClientId, Incoming)>) {
+    while let Ok(message) = read_message(&mut stream) {
+        if sender.send((id, Incoming::Message(message))).is_err() {
+            return;
+        }
+    }
+    let _ = sender.send((id, Incoming::Disconnected));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn join(table: &mut Table, id: ClientId, name: &str) {
+        table.handle(id, ClientMessage::Join { name: name.to_string() }).unwrap();
+    }
+
+    #[test]
+    fn players_take_a_seat_each_and_give_it_up_when_they_leave() {
+        let mut table = Table::new(TableRules::default(), 1);
+        join(&mut table, 1, "Sam");
+        join(&mut table, 2, "Alex");
+        assert_eq!(table.seat_of(1), Some(0));
+        assert_eq!(table.seat_of(2), Some(1));
+        assert_eq!(table.state_for(2).seats[1].name, "Alex");
+        assert_eq!(table.state_for(2).you, Some(1));
+        assert_eq!(
+            table.handle(1, ClientMessage::Join { name: "Sam".to_string() }),
+            Err("You already joined".to_string())
+        );
+        assert!(table.handle(3, ClientMessage::Join { name: "../x".to_string() }).is_err());
+
+        table.leave(2);
+        assert_eq!(table.seat_of(2), None);
+        // The empty seat on the right is taken away while the table is betting
+        assert_eq!(table.game().seats().len(), 1);
+        join(&mut table, 3, "Jo");
+        assert_eq!(table.seat_of(3), Some(1));
+    }
+
+    #[test]
+    fn a_player_who_leaves_takes_their_bet_back() {
+        let mut table = Table::new(TableRules::default(), 1);
+        join(&mut table, 1, "Sam");
+        join(&mut table, 2, "Alex");
+        table.handle(2, ClientMessage::Bet { amount: 25 }).unwrap();
+        table.handle(2, ClientMessage::Leave).unwrap();
+        assert_eq!(table.seat_of(2), None);
+        assert_eq!(table.game().seats().len(), 1);
+        assert_eq!(table.game().seats()[0].bet(), 0);
+        assert!(table.handle(1, ClientMessage::Deal).is_err());
+    }
+
+    #[test]
+    fn betting_before_joining_is_refused() {
+        let mut table = Table::new(TableRules::default(), 1);
+        assert_eq!(
+            table.handle(7, ClientMessage::Bet { amount: 25 }),
+            Err("Wait for a seat first".to_string())
+        );
+        assert_eq!(table.handle(7, ClientMessage::Hit), Err("Wait for a seat first".to_string()));
+        // Leaving without a seat does nothing
+        assert_eq!(table.handle(7, ClientMessage::Leave), Ok(()));
+    }
+
+    #[test]
+    fn only_the_player_whose_turn_it_is_can_play() {
+        let mut table = Table::new(TableRules::default(), 1);
+        join(&mut table, 1, "Sam");
+        join(&mut table, 2, "Alex");
+        table.handle(1, ClientMessage::Bet { amount: 25 }).unwrap();
+        table.handle(2, ClientMessage::Bet { amount: 25 }).unwrap();
+        table.handle(2, ClientMessage::Deal).unwrap();
+        assert_eq!(table.game().phase(), Phase::PlayerTurn);
+        assert_eq!(table.game().active_seat(), Some(0));
+        assert!(table.state_for(1).your_turn());
+        assert!(!table.state_for(2).your_turn());
+
+        assert_eq!(table.handle(2, ClientMessage::Stand), Err("It is not your turn".to_string()));
+        assert_eq!(table.game().active_seat(), Some(0));
+        table.handle(1, ClientMessage::Stand).unwrap();
+        assert_eq!(table.handle(1, ClientMessage::Hit), Err("It is not your turn".to_string()));
+    }
+
+    #[test]
+    fn a_player_who_joins_during_a_round_waits_for_a_free_seat() {
+        let mut table = Table::new(TableRules::default(), 1);
+        join(&mut table, 1, "Sam");
+        table.handle(1, ClientMessage::Bet { amount: 25 }).unwrap();
+        table.handle(1, ClientMessage::Deal).unwrap();
+        join(&mut table, 2, "Alex");
+        assert_eq!(table.seat_of(2), None);
+        assert_eq!(table.state_for(2).you, None);
+        assert_eq!(
+            table.handle(2, ClientMessage::Bet { amount: 25 }),
+            Err("Wait for a seat first".to_string())
+        );
+
+        // Playing the round out for the only player brings the table back to betting
+        table.leave(1);
+        assert_eq!(table.game().phase(), Phase::Betting);
+        assert_eq!(table.seat_of(2), Some(0));
+    }
+}
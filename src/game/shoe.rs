@@ -0,0 +1,280 @@
+/*
+Program Details: A dealing shoe holding one or more decks with a cut card
+
+In your game/mod.rs file add the following:
+    pub mod shoe;
+
+Then with the other use commands add:
+use blackjack3::game::shoe::Shoe;
+
+Usage examples:
+    // 6 decks, cut card placed after 75% of the shoe, seeded from the clock
+    let mut shoe = Shoe::new(6, 0.75, miniquad::date::now() as u64);
+    let card = shoe.deal();
+
+    // Between rounds the cards on the table go to the discards, then
+    // the shoe is reshuffled if the cut card came out during the last round
+    shoe.collect();
+    if shoe.reshuffle_if_needed() {
+        println!("Shuffling");
+    }
+
+    shoe.cards_remaining();  // cards left before the shoe is empty
+    shoe.decks_remaining();  // the same thing counted in decks, for the UI
+
+    // The shoe exactly as it was after a recorded number of shuffles and cards
+    let shoe = Shoe::resume(6, 0.75, seed, shuffle, dealt, &held);
+
+Shuffling uses its own RandGenerator seeded from the shoe seed and the number of
+shuffles so far, so the same seed always gives the same cards in the same order.
+
+A shoe that runs empty in the middle of a round is shuffled without the cards
+still on the table (everything dealt since the last collect()), so no card can
+show up twice. held() is the cards a shuffle was made without.
+*/
+use macroquad::rand::{ChooseRandom, RandGenerator};
+
+use crate::game::card::Card;
+
+pub const MIN_DECKS: usize = 1;
+pub const MAX_DECKS: usize = 8;
+
+#[derive(Debug, Clone)]
+pub struct Shoe {
+    decks: usize,
+    penetration: f32,
+    seed: u64,
+    shuffles: u64,
+    cards: Vec<Card>,   // Cards still in the shoe, dealt from the end
+    in_play: Vec<Card>, // Dealt since the last collect(), still on the table
+    held: Vec<Card>,    // Cards on the table when the last shuffle was made, left out of it
+    cut_card: usize,    // Reaching this many cards left brings out the cut card
+    cut_card_out: bool,
+}
+
+impl Shoe {
+    /// Builds and shuffles a shoe. Decks are kept between 1 and 8 and the
+    /// penetration (how much of the shoe is dealt before the cut card) between 10% and 100%.
+    pub fn new(decks: usize, penetration: f32, seed: u64) -> Self {
+        let decks = decks.clamp(MIN_DECKS, MAX_DECKS);
+        let mut shoe = Self {
+            decks,
+            penetration: penetration.clamp(0.1, 1.0),
+            seed,
+            shuffles: 0,
+            cards: Vec::with_capacity(decks * 52),
+            in_play: Vec::new(),
+            held: Vec::new(),
+            cut_card: 0,
+            cut_card_out: false,
+        };
+        shoe.shuffle();
+        shoe
+    }
+
+    /// Rebuilds a shoe part way through: the shuffle with this index (0 is the first), made
+    /// without the `held` cards, with `dealt` cards already taken, so it deals the same cards
+    /// as the original did from there
+    pub fn resume(decks: usize, penetration: f32, seed: u64, shuffle: u64, dealt: usize, held: &[Card]) -> Self {
+        let mut shoe = Self::new(decks, penetration, seed);
+        shoe.shuffles = shuffle;
+        shoe.in_play = held.to_vec();
+        shoe.shuffle();
+        for _ in 0..dealt.min(shoe.cards.len()) {
+            shoe.deal();
+        }
+        // The cards dealt before belong to earlier rounds
+        shoe.in_play.clear();
+        shoe
+    }
+
+    /// Puts every card back, except the ones still on the table, and shuffles the shoe
+    pub fn shuffle(&mut self) {
+        self.cards.clear();
+        for _ in 0..self.decks {
+            self.cards.extend(Card::full_deck());
+        }
+        // A table holding every card (never collected) leaves nothing to shuffle, so it all comes back
+        if self.in_play.len() >= self.cards.len() {
+            self.in_play.clear();
+        }
+        for card in &self.in_play {
+            if let Some(index) = self.cards.iter().position(|left| left == card) {
+                self.cards.swap_remove(index);
+            }
+        }
+        self.held = self.in_play.clone();
+
+        let rng = RandGenerator::new();
+        rng.srand(self.seed.wrapping_add(self.shuffles));
+        self.cards.shuffle_with_state(&rng);
+        self.shuffles += 1;
+
+        let total = self.cards.len();
+        let dealt_before_cut = (total as f32 * self.penetration).round() as usize;
+        self.cut_card = total - dealt_before_cut.min(total);
+        self.cut_card_out = false;
+    }
+
+    /// Takes the next card. An empty shoe is reshuffled first (without the cards on the table)
+    /// so a round never runs out.
+    pub fn deal(&mut self) -> Card {
+        if self.cards.is_empty() {
+            self.shuffle();
+        }
+        let card = self.cards.pop().unwrap();
+        if self.cards.len() <= self.cut_card {
+            self.cut_card_out = true;
+        }
+        self.in_play.push(card);
+        card
+    }
+
+    /// The cards on the table go to the discards, call it when a round is cleared away
+    pub fn collect(&mut self) {
+        self.in_play.clear();
+    }
+
+    /// The cards the current shuffle was made without, they were on the table when it happened
+    pub fn held(&self) -> &[Card] {
+        &self.held
+    }
+
+    /// True once the cut card has come out and the shoe should be shuffled before the next round
+    pub fn needs_shuffle(&self) -> bool {
+        self.cut_card_out
+    }
+
+    /// Shuffles if the cut card is out, returns true when it did
+    pub fn reshuffle_if_needed(&mut self) -> bool {
+        if self.needs_shuffle() {
+            self.shuffle();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn cards_remaining(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn decks_remaining(&self) -> f32 {
+        self.cards.len() as f32 / 52.0
+    }
+
+    /// How many cards of each value are left (aces first, tens and faces together at the end),
+    /// without giving away the order they come out in
+    pub fn remaining_by_value(&self) -> [u32; 10] {
+        let mut counts = [0; 10];
+        for card in &self.cards {
+            counts[card.rank.value() as usize - 1] += 1;
+        }
+        counts
+    }
+
+    /// Index of the shuffle the cards are coming from, 0 for the first
+    pub fn shuffle_number(&self) -> u64 {
+        self.shuffles - 1
+    }
+
+    /// Cards taken since the last shuffle
+    pub fn cards_dealt(&self) -> usize {
+        self.decks * 52 - self.held.len() - self.cards.len()
+    }
+
+    pub fn decks(&self) -> usize {
+        self.decks
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+#[cfg(test)]
+impl Shoe {
+    /// Moves these cards to the top of the shoe so they come out next, in this order.
+    /// Tests use it to set up a hand.
+    pub(crate) fn stack(&mut self, cards: &[Card]) {
+        for card in cards.iter().rev() {
+            let index = self.cards.iter().position(|left| left == card).expect("card is not in the shoe");
+            let card = self.cards.remove(index);
+            self.cards.push(card);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    // Deals one round worth of cards: the shoe runs out part way through
+    fn deal_past_the_end(shoe: &mut Shoe, table: usize) -> Vec<Card> {
+        while shoe.cards_remaining() > 3 {
+            shoe.deal();
+        }
+        shoe.collect();
+        (0..table).map(|_| shoe.deal()).collect()
+    }
+
+    #[test]
+    fn mid_round_reshuffle_leaves_the_table_out() {
+        let mut shoe = Shoe::new(1, 0.75, 7);
+        let table = deal_past_the_end(&mut shoe, 10);
+        assert_eq!(shoe.held().len(), 3);
+        assert_eq!(shoe.cards_remaining(), 52 - 10);
+        // No card of the single deck shows up twice on the table or in the shoe
+        let mut seen = HashMap::new();
+        for card in table.iter().chain(&shoe.cards) {
+            *seen.entry(*card).or_insert(0) += 1;
+        }
+        assert_eq!(seen.len(), 52);
+        assert!(seen.values().all(|count| *count == 1));
+    }
+
+    #[test]
+    fn resume_deals_the_same_cards_after_a_mid_round_reshuffle() {
+        let mut shoe = Shoe::new(1, 0.75, 7);
+        deal_past_the_end(&mut shoe, 10);
+        let mut resumed = Shoe::resume(1, 0.75, 7, shoe.shuffle_number(), shoe.cards_dealt(), shoe.held());
+        assert_eq!(resumed.cards_dealt(), shoe.cards_dealt());
+        for _ in 0..20 {
+            assert_eq!(resumed.deal(), shoe.deal());
+        }
+    }
+
+    #[test]
+    fn the_cut_card_comes_out_at_the_penetration() {
+        let mut shoe = Shoe::new(1, 0.5, 3);
+        for _ in 0..25 {
+            shoe.deal();
+        }
+        assert!(!shoe.needs_shuffle());
+        shoe.deal();
+        assert!(shoe.needs_shuffle());
+        shoe.collect();
+        assert!(shoe.reshuffle_if_needed());
+        assert_eq!(shoe.cards_remaining(), 52);
+        assert!(!shoe.needs_shuffle());
+        assert!(!shoe.reshuffle_if_needed());
+    }
+
+    #[test]
+    fn decks_are_kept_between_one_and_eight() {
+        assert_eq!(Shoe::new(0, 0.75, 1).cards_remaining(), 52);
+        assert_eq!(Shoe::new(20, 0.75, 1).cards_remaining(), 8 * 52);
+        assert_eq!(Shoe::new(6, 0.75, 1).decks_remaining(), 6.0);
+    }
+
+    #[test]
+    fn the_same_seed_deals_the_same_cards() {
+        let mut first = Shoe::new(2, 0.75, 42);
+        let mut second = Shoe::new(2, 0.75, 42);
+        let mut other = Shoe::new(2, 0.75, 43);
+        let dealt: Vec<Card> = (0..104).map(|_| first.deal()).collect();
+        assert_eq!(dealt, (0..104).map(|_| second.deal()).collect::<Vec<_>>());
+        assert_ne!(dealt, (0..104).map(|_| other.deal()).collect::<Vec<_>>());
+    }
+}
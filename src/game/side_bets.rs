@@ -0,0 +1,258 @@
+/*
+Program Details: Side bets paid from the first cards of the round
+
+In your game/mod.rs file add the following:
+    pub mod side_bets;
+
+Then with the other use commands add:
+use blackjack3::game::side_bets::{SideBet, SideBetPaytables, SideBetResult};
+
+Usage examples:
+    game.place_side_bet_at(seat, SideBet::PerfectPairs, 5)?;   // while betting, next to the main bet
+    game.place_side_bet_at(seat, SideBet::TwentyOnePlusThree, 5)?;
+    game.deal()?;   // side bets are settled straight after the deal, SideBetSettled events say how
+
+    // Or look at cards yourself
+    perfect_pairs(first, second);                 // Some(SideBetResult::ColoredPair)
+    twenty_one_plus_three(first, second, up);     // Some(SideBetResult::Flush)
+    rules.side_bets.pays(SideBetResult::Flush);   // 5, as in 5 to 1
+
+Perfect Pairs looks at the player's first two cards:
+    Mixed pair      same rank, one red and one black
+    Colored pair    same rank and colour, different suits
+    Perfect pair    same rank and suit
+
+21+3 looks at the player's first two cards and the dealer's up card as a poker hand:
+    Flush           all one suit
+    Straight        three ranks in a row, the ace goes high (Q K A) or low (A 2 3)
+    Three of a kind the same rank, not all one suit
+    Straight flush  a straight all in one suit
+    Suited trips    the same rank and suit
+
+Only the best result is paid. The paytables are part of the table rules.
+*/
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::card::Card;
+
+/// The side bets a seat can make
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SideBet {
+    PerfectPairs,
+    TwentyOnePlusThree,
+}
+
+impl SideBet {
+    pub const ALL: [SideBet; 2] = [SideBet::PerfectPairs, SideBet::TwentyOnePlusThree];
+}
+
+impl fmt::Display for SideBet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SideBet::PerfectPairs => write!(f, "Perfect Pairs"),
+            SideBet::TwentyOnePlusThree => write!(f, "21+3"),
+        }
+    }
+}
+
+/// Winning hands for the side bets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SideBetResult {
+    MixedPair,
+    ColoredPair,
+    PerfectPair,
+    Flush,
+    Straight,
+    ThreeOfAKind,
+    StraightFlush,
+    SuitedTrips,
+}
+
+impl fmt::Display for SideBetResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SideBetResult::MixedPair => write!(f, "Mixed pair"),
+            SideBetResult::ColoredPair => write!(f, "Colored pair"),
+            SideBetResult::PerfectPair => write!(f, "Perfect pair"),
+            SideBetResult::Flush => write!(f, "Flush"),
+            SideBetResult::Straight => write!(f, "Straight"),
+            SideBetResult::ThreeOfAKind => write!(f, "Three of a kind"),
+            SideBetResult::StraightFlush => write!(f, "Straight flush"),
+            SideBetResult::SuitedTrips => write!(f, "Suited trips"),
+        }
+    }
+}
+
+/// What each side bet result pays, as "x to 1"
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SideBetPaytables {
+    /// (default: 5)
+    pub mixed_pair: u32,
+    /// (default: 12)
+    pub colored_pair: u32,
+    /// (default: 25)
+    pub perfect_pair: u32,
+    /// (default: 5)
+    pub flush: u32,
+    /// (default: 10)
+    pub straight: u32,
+    /// (default: 30)
+    pub three_of_a_kind: u32,
+    /// (default: 40)
+    pub straight_flush: u32,
+    /// (default: 100)
+    pub suited_trips: u32,
+}
+
+impl Default for SideBetPaytables {
+    fn default() -> Self {
+        Self {
+            mixed_pair: 5,
+            colored_pair: 12,
+            perfect_pair: 25,
+            flush: 5,
+            straight: 10,
+            three_of_a_kind: 30,
+            straight_flush: 40,
+            suited_trips: 100,
+        }
+    }
+}
+
+impl SideBetPaytables {
+    pub fn pays(&self, result: SideBetResult) -> u32 {
+        match result {
+            SideBetResult::MixedPair => self.mixed_pair,
+            SideBetResult::ColoredPair => self.colored_pair,
+            SideBetResult::PerfectPair => self.perfect_pair,
+            SideBetResult::Flush => self.flush,
+            SideBetResult::Straight => self.straight,
+            SideBetResult::ThreeOfAKind => self.three_of_a_kind,
+            SideBetResult::StraightFlush => self.straight_flush,
+            SideBetResult::SuitedTrips => self.suited_trips,
+        }
+    }
+
+    /// Chips back for a settled side bet, the stake included, 0 when it lost.
+    /// A payout too big for a u32 stops at u32::MAX.
+    pub fn payout(&self, amount: u32, result: Option<SideBetResult>) -> u32 {
+        result.map_or(0, |result| amount.saturating_mul(self.pays(result)).saturating_add(amount))
+    }
+}
+
+/// A side bet at a seat and, once the cards are out, how it did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SideBetStake {
+    pub bet: SideBet,
+    pub amount: u32,
+    /// None while waiting for the deal, and for a side bet that lost
+    pub result: Option<SideBetResult>,
+    pub payout: u32,
+}
+
+/// Works out a side bet from the player's first two cards and the dealer's up card
+pub fn resolve(bet: SideBet, first: Card, second: Card, dealer_up: Card) -> Option<SideBetResult> {
+    match bet {
+        SideBet::PerfectPairs => perfect_pairs(first, second),
+        SideBet::TwentyOnePlusThree => twenty_one_plus_three(first, second, dealer_up),
+    }
+}
+
+pub fn perfect_pairs(first: Card, second: Card) -> Option<SideBetResult> {
+    if first.rank != second.rank {
+        None
+    } else if first.suit == second.suit {
+        Some(SideBetResult::PerfectPair)
+    } else if first.suit.is_red() == second.suit.is_red() {
+        Some(SideBetResult::ColoredPair)
+    } else {
+        Some(SideBetResult::MixedPair)
+    }
+}
+
+pub fn twenty_one_plus_three(first: Card, second: Card, dealer_up: Card) -> Option<SideBetResult> {
+    let cards = [first, second, dealer_up];
+    let flush = cards.iter().all(|card| card.suit == first.suit);
+    let trips = cards.iter().all(|card| card.rank == first.rank);
+
+    // Rank::index() has the ace at 0 and the king at 12, Q K A is the one straight that wraps
+    let mut ranks: Vec<usize> = cards.iter().map(|card| card.rank.index()).collect();
+    ranks.sort_unstable();
+    let straight = (ranks[1] == ranks[0] + 1 && ranks[2] == ranks[1] + 1) || ranks == [0, 11, 12];
+
+    match (trips, straight, flush) {
+        (true, _, true) => Some(SideBetResult::SuitedTrips),
+        (_, true, true) => Some(SideBetResult::StraightFlush),
+        (true, _, false) => Some(SideBetResult::ThreeOfAKind),
+        (_, true, false) => Some(SideBetResult::Straight),
+        (_, _, true) => Some(SideBetResult::Flush),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(text: &str) -> Card {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn perfect_pairs_results() {
+        assert_eq!(perfect_pairs(card("8H"), card("8H")), Some(SideBetResult::PerfectPair));
+        assert_eq!(perfect_pairs(card("8H"), card("8D")), Some(SideBetResult::ColoredPair));
+        assert_eq!(perfect_pairs(card("8H"), card("8S")), Some(SideBetResult::MixedPair));
+        assert_eq!(perfect_pairs(card("8H"), card("9H")), None);
+        // Tens and kings count the same in blackjack but are not a pair
+        assert_eq!(perfect_pairs(card("10H"), card("KH")), None);
+    }
+
+    #[test]
+    fn twenty_one_plus_three_results() {
+        assert_eq!(
+            twenty_one_plus_three(card("7C"), card("7C"), card("7C")),
+            Some(SideBetResult::SuitedTrips)
+        );
+        assert_eq!(
+            twenty_one_plus_three(card("7C"), card("8C"), card("9C")),
+            Some(SideBetResult::StraightFlush)
+        );
+        assert_eq!(
+            twenty_one_plus_three(card("7C"), card("7D"), card("7S")),
+            Some(SideBetResult::ThreeOfAKind)
+        );
+        assert_eq!(twenty_one_plus_three(card("9C"), card("7D"), card("8S")), Some(SideBetResult::Straight));
+        assert_eq!(twenty_one_plus_three(card("2C"), card("9C"), card("KC")), Some(SideBetResult::Flush));
+        assert_eq!(twenty_one_plus_three(card("2C"), card("9D"), card("KC")), None);
+    }
+
+    #[test]
+    fn aces_make_straights_high_and_low_but_do_not_wrap() {
+        assert_eq!(twenty_one_plus_three(card("QC"), card("KD"), card("AS")), Some(SideBetResult::Straight));
+        assert_eq!(twenty_one_plus_three(card("AC"), card("2D"), card("3S")), Some(SideBetResult::Straight));
+        assert_eq!(twenty_one_plus_three(card("KC"), card("AD"), card("2S")), None);
+    }
+
+    #[test]
+    fn payout_includes_the_stake_and_a_loss_pays_nothing() {
+        let paytables = SideBetPaytables::default();
+        assert_eq!(paytables.payout(5, Some(SideBetResult::MixedPair)), 30);
+        assert_eq!(paytables.payout(5, Some(SideBetResult::SuitedTrips)), 505);
+        assert_eq!(paytables.payout(5, None), 0);
+        assert_eq!(
+            paytables.payout(5, resolve(SideBet::TwentyOnePlusThree, card("2C"), card("9C"), card("KC"))),
+            30
+        );
+    }
+
+    #[test]
+    fn a_huge_payout_stops_at_the_most_chips() {
+        let paytables = SideBetPaytables::default();
+        assert_eq!(paytables.payout(u32::MAX / 2, Some(SideBetResult::SuitedTrips)), u32::MAX);
+        assert_eq!(paytables.payout(u32::MAX, Some(SideBetResult::MixedPair)), u32::MAX);
+    }
+}
@@ -0,0 +1,192 @@
+/*
+Program Details: Statistics for a session, worked out from its hand history
+
+In your game/mod.rs file add the following:
+    pub mod stats;
+
+Then with the other use commands add:
+use blackjack3::game::stats::SessionStats;
+
+Usage examples:
+    // The entries written to the hand history this session, and the chips at the start
+    let stats = SessionStats::from_history(500, &session);
+
+    println!("{} hands, {} won, net {}", stats.hands, stats.won, stats.net);
+    stats.bankroll;   // chips at the start and after every round, for a chart
+
+A round's streak is decided by its net: up is a win, down is a loss and
+breaking even (a push) leaves the streak as it was.
+*/
+use crate::game::history::HistoryEntry;
+
+/// Totals for the rounds played this session
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionStats {
+    pub rounds: u32,
+    /// Counts each split hand on its own
+    pub hands: u32,
+    pub won: u32,
+    pub lost: u32,
+    pub pushed: u32,
+    pub blackjacks: u32,
+    pub surrenders: u32,
+    pub doubles: u32,
+    pub doubles_won: u32,
+    /// Hands that came from a split
+    pub split_hands: u32,
+    pub split_hands_won: u32,
+    pub net: i64,
+    /// Best and worst single round, 0 when there was none
+    pub biggest_win: i64,
+    pub biggest_loss: i64,
+    pub longest_win_streak: u32,
+    pub longest_loss_streak: u32,
+    /// Chips at the start of the session and after every round
+    pub bankroll: Vec<u32>,
+}
+
+impl SessionStats {
+    pub fn from_history(start_bankroll: u32, entries: &[HistoryEntry]) -> Self {
+        let mut stats = Self {
+            bankroll: vec![start_bankroll],
+            ..Default::default()
+        };
+        let mut win_streak = 0;
+        let mut loss_streak = 0;
+
+        for entry in entries {
+            stats.rounds += 1;
+            let split = entry.outcomes.len() > 1;
+            for (i, outcome) in entry.outcomes.iter().enumerate() {
+                let won = outcome == "win" || outcome == "blackjack";
+                match outcome.as_str() {
+                    "blackjack" => {
+                        stats.won += 1;
+                        stats.blackjacks += 1;
+                    }
+                    "win" => stats.won += 1,
+                    "push" => stats.pushed += 1,
+                    "surrender" => stats.surrenders += 1,
+                    "lose" | "bust" => stats.lost += 1,
+                    // A blank or unknown outcome (a damaged history line) is not a hand played
+                    _ => continue,
+                }
+                stats.hands += 1;
+                // A hand with more than the first bet on it was doubled
+                if entry.hand_bets.get(i).is_some_and(|bet| *bet > entry.bet) {
+                    stats.doubles += 1;
+                    stats.doubles_won += won as u32;
+                }
+                if split {
+                    stats.split_hands += 1;
+                    stats.split_hands_won += won as u32;
+                }
+            }
+
+            stats.net += entry.net;
+            stats.biggest_win = stats.biggest_win.max(entry.net);
+            stats.biggest_loss = stats.biggest_loss.max(-entry.net);
+            if entry.net > 0 {
+                win_streak += 1;
+                loss_streak = 0;
+            } else if entry.net < 0 {
+                loss_streak += 1;
+                win_streak = 0;
+            }
+            stats.longest_win_streak = stats.longest_win_streak.max(win_streak);
+            stats.longest_loss_streak = stats.longest_loss_streak.max(loss_streak);
+            stats.bankroll.push(entry.bankroll);
+        }
+        stats
+    }
+
+    /// Fraction of hands won, pushes and surrenders included in the count
+    pub fn win_rate(&self) -> f32 {
+        if self.hands == 0 {
+            0.0
+        } else {
+            self.won as f32 / self.hands as f32
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A one hand round, the bankroll after it goes up by the net
+    fn round(outcome: &str, net: i64, bankroll: u32) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: 0,
+            seat: 0,
+            seed: 1,
+            shuffle: 0,
+            position: 0,
+            bet: 10,
+            player_cards: Vec::new(),
+            dealer_cards: Vec::new(),
+            actions: Vec::new(),
+            outcomes: vec![outcome.to_string()],
+            hand_bets: vec![10],
+            insurance: 0,
+            payout: 0,
+            net,
+            bankroll,
+            side_bets: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn streaks_carry_over_pushes() {
+        let entries = [
+            round("win", 10, 510),
+            round("blackjack", 15, 525),
+            round("push", 0, 525),
+            round("win", 10, 535),
+            round("lose", -10, 525),
+            round("bust", -10, 515),
+            round("win", 10, 525),
+        ];
+        let stats = SessionStats::from_history(500, &entries);
+        assert_eq!(stats.longest_win_streak, 3);
+        assert_eq!(stats.longest_loss_streak, 2);
+        assert_eq!(stats.net, 25);
+        assert_eq!(stats.biggest_win, 15);
+        assert_eq!(stats.biggest_loss, 10);
+        assert_eq!(stats.bankroll, vec![500, 510, 525, 525, 535, 525, 515, 525]);
+    }
+
+    #[test]
+    fn win_rate_counts_every_hand() {
+        let entries = [
+            round("win", 10, 510),
+            round("push", 0, 510),
+            round("surrender", -5, 505),
+            round("lose", -10, 495),
+        ];
+        let stats = SessionStats::from_history(500, &entries);
+        assert_eq!((stats.won, stats.pushed, stats.surrenders, stats.lost), (1, 1, 1, 1));
+        assert_eq!(stats.win_rate(), 0.25);
+        assert_eq!(SessionStats::from_history(500, &[]).win_rate(), 0.0);
+    }
+
+    #[test]
+    fn unknown_outcomes_are_not_losses() {
+        let entries = [round("win", 10, 510), round("", 0, 510), round("???", 0, 510)];
+        let stats = SessionStats::from_history(500, &entries);
+        assert_eq!(stats.lost, 0);
+        assert_eq!(stats.hands, 1);
+        assert_eq!(stats.win_rate(), 1.0);
+    }
+
+    #[test]
+    fn doubles_and_split_hands_are_counted_on_their_own() {
+        let mut split = round("win", 10, 510);
+        split.outcomes = vec!["win".to_string(), "lose".to_string()];
+        split.hand_bets = vec![20, 10];
+        let stats = SessionStats::from_history(500, &[split]);
+        assert_eq!((stats.rounds, stats.hands), (1, 2));
+        assert_eq!((stats.doubles, stats.doubles_won), (1, 1));
+        assert_eq!((stats.split_hands, stats.split_hands_won), (2, 1));
+    }
+}
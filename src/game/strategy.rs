@@ -0,0 +1,302 @@
+/*
+Program Details: Basic strategy advisor
+
+Works out the basic strategy play for a hand against the dealer's up card.
+Nothing is hard coded to one table, the chart changes with the TableRules:
+    H17/S17            soft 18 and soft 19 doubles, 11 vs ace, surrender spots
+    double after split which small pairs are worth splitting
+    number of decks    9 vs 2, 8 vs 5 and 6, 11 vs ace in 1 and 2 deck games
+    surrender          none, late or early
+
+In your game/mod.rs file add the following:
+    pub mod strategy;
+
+Then with the other use commands add:
+use blackjack3::game::strategy::{advise, Play};
+
+Usage examples:
+    // The play for the active hand, None when it is not the player's turn
+    if let Some(play) = advise(&game) {
+        println!("Basic strategy says {}", play);
+    }
+
+    // Any hand, when you already know which plays are allowed
+    let play = basic_strategy(&hand, Rank::Six, &rules, Allowed::all());
+
+The advice only ever picks a play that is allowed. When the chart says double
+but the hand cannot double, it falls back to hitting (standing on soft 18 and 19).
+*/
+use std::fmt;
+
+use crate::game::card::Rank;
+use crate::game::engine::{BlackjackGame, Phase};
+use crate::game::hand::Hand;
+use crate::game::rules::{Surrender, TableRules};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Play {
+    Hit,
+    Stand,
+    Double,
+    Split,
+    Surrender,
+}
+
+impl fmt::Display for Play {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Play::Hit => write!(f, "Hit"),
+            Play::Stand => write!(f, "Stand"),
+            Play::Double => write!(f, "Double"),
+            Play::Split => write!(f, "Split"),
+            Play::Surrender => write!(f, "Surrender"),
+        }
+    }
+}
+
+/// Which plays the hand is allowed to make right now
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Allowed {
+    pub hit: bool,
+    pub double: bool,
+    pub split: bool,
+    pub surrender: bool,
+}
+
+impl Allowed {
+    pub fn all() -> Self {
+        Self {
+            hit: true,
+            double: true,
+            split: true,
+            surrender: true,
+        }
+    }
+}
+
+/// Basic strategy for the active seat's hand, None outside the players' turn.
+/// Before the peek against a ten it is Surrender, or Stand for keeping the hand.
+pub fn advise(game: &BlackjackGame) -> Option<Play> {
+    let seat = game.active_seat()?;
+    if game.phase() == Phase::Dealing {
+        let hand = &game.seats()[seat].hands()[0].hand;
+        let surrender = game.can_surrender() && should_surrender(hand, 10, game.rules());
+        return Some(if surrender { Play::Surrender } else { Play::Stand });
+    }
+    if game.phase() != Phase::PlayerTurn {
+        return None;
+    }
+    let active = game.active_hand()?;
+    let up = game.dealer().cards().first()?.rank;
+    let allowed = Allowed {
+        hit: game.can_hit(),
+        double: game.can_double(),
+        split: game.can_split(),
+        surrender: game.can_surrender(),
+    };
+    Some(basic_strategy(&game.seats()[seat].hands()[active].hand, up, game.rules(), allowed))
+}
+
+/// The basic strategy play for a hand against the dealer's up card at a table with these rules
+pub fn basic_strategy(hand: &Hand, up: Rank, rules: &TableRules, allowed: Allowed) -> Play {
+    // Split aces and other hands that cannot take a card just stand
+    if !allowed.hit {
+        return Play::Stand;
+    }
+    // Ace counts as 11 here so the charts read 2 to 11
+    let dealer = if up.is_ace() { 11 } else { up.value() };
+
+    if allowed.surrender && should_surrender(hand, dealer, rules) {
+        return Play::Surrender;
+    }
+    if allowed.split && should_split(hand, dealer, rules) {
+        return Play::Split;
+    }
+    if hand.is_soft() {
+        soft_play(hand.best_total(), dealer, rules, allowed.double)
+    } else {
+        hard_play(hand.hard_total(), dealer, rules, allowed.double)
+    }
+}
+
+// True for a table with one or two decks, where a few doubles change
+fn few_decks(rules: &TableRules) -> bool {
+    rules.decks <= 2
+}
+
+fn should_surrender(hand: &Hand, dealer: u8, rules: &TableRules) -> bool {
+    if hand.len() != 2 || hand.is_soft() {
+        return false;
+    }
+    let cards = hand.cards();
+    let pair = cards[0].rank.value() == cards[1].rank.value();
+    let total = hand.hard_total();
+    let h17 = rules.dealer_hits_soft_17;
+
+    match rules.surrender {
+        Surrender::None => false,
+        // Early surrender comes before the peek, so giving up against an ace or ten is worth a lot more
+        Surrender::Early => match dealer {
+            11 => (5..=7).contains(&total) || (12..=17).contains(&total),
+            10 => (14..=16).contains(&total) && !(pair && total == 16),
+            9 => total == 16 && !pair,
+            _ => false,
+        },
+        Surrender::Late => {
+            if pair {
+                // 8,8 is split except against an ace at a multi deck H17 table
+                return total == 16 && dealer == 11 && h17 && !few_decks(rules);
+            }
+            match (total, dealer) {
+                (16, 10) | (16, 11) => true,
+                (16, 9) => !few_decks(rules),
+                (15, 10) => true,
+                (15, 11) | (17, 11) => h17,
+                _ => false,
+            }
+        }
+    }
+}
+
+fn should_split(hand: &Hand, dealer: u8, rules: &TableRules) -> bool {
+    if hand.len() != 2 {
+        return false;
+    }
+    let cards = hand.cards();
+    if cards[0].rank.value() != cards[1].rank.value() {
+        return false;
+    }
+    let das = rules.double_after_split;
+    match cards[0].rank.value() {
+        1 | 8 => true,
+        9 => matches!(dealer, 2..=6 | 8 | 9),
+        7 => dealer <= 7,
+        6 => dealer <= 6 && (das || dealer >= 3),
+        4 => das && (dealer == 5 || dealer == 6),
+        3 | 2 => dealer <= 7 && (das || dealer >= 4),
+        // 5,5 plays as a hard 10 and tens are never split
+        _ => false,
+    }
+}
+
+fn soft_play(total: u8, dealer: u8, rules: &TableRules, can_double: bool) -> Play {
+    let h17 = rules.dealer_hits_soft_17;
+    let (double_range, otherwise) = match total {
+        20 | 21 => return Play::Stand,
+        19 => (if h17 { 6..=6 } else { 0..=0 }, Play::Stand),
+        18 => {
+            let otherwise = if dealer >= 9 { Play::Hit } else { Play::Stand };
+            (if h17 { 2..=6 } else { 3..=6 }, otherwise)
+        }
+        17 => (3..=6, Play::Hit),
+        15 | 16 => (4..=6, Play::Hit),
+        13 | 14 => (5..=6, Play::Hit),
+        // Two aces that cannot be split
+        _ => return Play::Hit,
+    };
+    if double_range.contains(&dealer) {
+        if can_double {
+            return Play::Double;
+        }
+        // Soft 18 and 19 would rather stand than hit when they cannot double
+        if total >= 18 {
+            return Play::Stand;
+        }
+        return Play::Hit;
+    }
+    otherwise
+}
+
+fn hard_play(total: u8, dealer: u8, rules: &TableRules, can_double: bool) -> Play {
+    let double = |wanted: bool| {
+        if wanted && can_double {
+            Play::Double
+        } else {
+            Play::Hit
+        }
+    };
+    match total {
+        17.. => Play::Stand,
+        13..=16 => {
+            if dealer <= 6 {
+                Play::Stand
+            } else {
+                Play::Hit
+            }
+        }
+        12 => {
+            if (4..=6).contains(&dealer) {
+                Play::Stand
+            } else {
+                Play::Hit
+            }
+        }
+        11 => double(dealer <= 10 || rules.dealer_hits_soft_17 || few_decks(rules)),
+        10 => double(dealer <= 9),
+        9 => double((3..=6).contains(&dealer) || (dealer == 2 && few_decks(rules))),
+        8 => double(few_decks(rules) && (5..=6).contains(&dealer)),
+        _ => Play::Hit,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::card::Card;
+
+    fn play(text: &str, up: Rank, rules: &TableRules, allowed: Allowed) -> Play {
+        let cards: Vec<Card> = text.split_whitespace().map(|card| card.parse().unwrap()).collect();
+        basic_strategy(&Hand::from_cards(&cards), up, rules, allowed)
+    }
+
+    fn s17() -> TableRules {
+        TableRules {
+            dealer_hits_soft_17: false,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn six_deck_chart_spots() {
+        let h17 = TableRules::default();
+        assert_eq!(play("10S 2H", Rank::Three, &h17, Allowed::all()), Play::Hit);
+        assert_eq!(play("10S 2H", Rank::Four, &h17, Allowed::all()), Play::Stand);
+        assert_eq!(play("10S 10H", Rank::Six, &h17, Allowed::all()), Play::Stand);
+        assert_eq!(play("AS AH", Rank::Six, &h17, Allowed::all()), Play::Split);
+        assert_eq!(play("8S 8H", Rank::Ten, &h17, Allowed::all()), Play::Split);
+        assert_eq!(play("10S 6H", Rank::Ten, &h17, Allowed::all()), Play::Surrender);
+    }
+
+    #[test]
+    fn h17_changes_the_chart() {
+        let h17 = TableRules::default();
+        assert_eq!(play("6S 5H", Rank::Ace, &h17, Allowed::all()), Play::Double);
+        assert_eq!(play("6S 5H", Rank::Ace, &s17(), Allowed::all()), Play::Hit);
+        assert_eq!(play("AS 7H", Rank::Two, &h17, Allowed::all()), Play::Double);
+        assert_eq!(play("AS 7H", Rank::Two, &s17(), Allowed::all()), Play::Stand);
+        assert_eq!(play("8S 8H", Rank::Ace, &h17, Allowed::all()), Play::Surrender);
+        assert_eq!(play("8S 8H", Rank::Ace, &s17(), Allowed::all()), Play::Split);
+    }
+
+    #[test]
+    fn falls_back_to_an_allowed_play() {
+        let rules = TableRules::default();
+        let no_extras = Allowed {
+            hit: true,
+            double: false,
+            split: false,
+            surrender: false,
+        };
+        assert_eq!(play("10S 6H", Rank::Ten, &rules, no_extras), Play::Hit);
+        assert_eq!(play("6S 5H", Rank::Six, &rules, no_extras), Play::Hit);
+        assert_eq!(play("AS 7H", Rank::Six, &rules, no_extras), Play::Stand);
+        let no_surrender = TableRules {
+            surrender: Surrender::None,
+            ..Default::default()
+        };
+        assert_eq!(play("10S 6H", Rank::Ten, &no_surrender, Allowed::all()), Play::Hit);
+        // Split aces cannot take another card
+        let split_aces = Allowed { hit: false, ..no_extras };
+        assert_eq!(play("AS 5H", Rank::Six, &rules, split_aces), Play::Stand);
+    }
+}
@@ -0,0 +1,14 @@
+/*
+--------------------------------------------
+lib.rs
+The blackjack game logic as a library, so more than one program can use it.
+
+The GUI (main.rs) and the simulator (bin/simulate.rs) both play with the
+same engine, hand totals and payouts from here, so anything the simulator
+measures is exactly what the table pays.
+
+Import from a binary with:
+use blackjack3::game::engine::BlackjackGame;
+--------------------------------------------
+*/
+pub mod game;
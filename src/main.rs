@@ -6,18 +6,53 @@ Program Details: Blackjack
 
 mod modules;
 
+use std::net::TcpStream;
+use std::path::Path;
 use std::process::exit;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
 
+use blackjack3::game::ai::{AiPlayer, AiStyle, SeatPlayer};
+use blackjack3::game::card::{Card, CARD_BACK};
+use blackjack3::game::counting::{system_by_name, CardCounter, HiLo, QuizScore, SYSTEM_NAMES};
+use blackjack3::game::engine::{BlackjackGame, GameEvent, Outcome, Phase, PlayerHand, Seat, MAX_SEATS};
+use blackjack3::game::hand::Hand;
+use blackjack3::game::history::{HistoryEntry, HistoryWriter};
+use blackjack3::game::limits::{TableLimits, LOBBY};
+use blackjack3::game::net::{read_message, write_message, ClientMessage, SeatState, ServerMessage, TableState};
+use blackjack3::game::odds::{odds_for, DealerOdds, PlayerEv};
+use blackjack3::game::profile::{KeyBindings, Profile, ProfileError, ProfileStore, STARTING_CHIPS};
+use blackjack3::game::replay::{Replay, RoundRecord};
+use blackjack3::game::shoe::Shoe;
+use blackjack3::game::side_bets::SideBet;
+use blackjack3::game::stats::SessionStats;
+use blackjack3::game::strategy::{advise, Play};
+use crate::modules::chips::{ChipClick, ChipRack, ChipStack};
+use crate::modules::hand_view::HandView;
 use crate::modules::label::Label;
+use crate::modules::line_chart::LineChart;
 use crate::modules::messagebox::{MessageBox, MessageBoxResult};
 use crate::modules::preload_image::LoadingScreenOptions;
 use crate::modules::preload_image::TextureManager;
 use crate::modules::scale::use_virtual_resolution;
-use crate::modules::still_image::StillImage;
+use crate::modules::seat_view::SeatView;
 use crate::modules::text_button::TextButton;
 use crate::modules::text_input::TextInput;
 use macroquad::prelude::*;
-use macroquad::rand::ChooseRandom;
+
+// Names for the computer players, one for each seat
+const AI_NAMES: [&str; MAX_SEATS] = ["Ann", "Ben", "Cal", "Dee", "Eve", "Fin", "Gus"];
+// Computer players bet in units of this many chips
+const AI_UNIT: u32 = 10;
+// Seconds a computer player waits before it acts, so the player can follow what it did
+const AI_DELAY: f64 = 0.6;
+// Where dealt cards slide in from, just under the shoe count
+const SHOE_SPOT: (f32, f32) = (870.0, 70.0);
+// Deal speeds the Deal button goes through, seconds for each card
+const DEAL_SPEEDS: [(&str, f32); 4] = [("Fast", 0.15), ("Normal", 0.3), ("Slow", 0.5), ("Off", 0.0)];
+// Saved players on each page of the profile screen
+const PROFILES_PER_PAGE: usize = 8;
+
 /// Set up window settings before the app runs
 fn window_conf() -> Conf {
     Conf {
@@ -34,12 +69,8 @@ fn window_conf() -> Conf {
 
 #[macroquad::main(window_conf)]
 async fn main() {
-    // Create card deck data
-    let mut deck = deck_reset();
-  
-
-    // Add backcard to the full list of assets
-    let all_assets = [&deck[..], &["assets/backcard.png"]].concat();
+    // Every card image plus the backcard
+    let all_assets = Card::all_asset_paths();
 
     // Create the texture manager
     let tm = TextureManager::new();
@@ -57,21 +88,103 @@ async fn main() {
     // Pass the &all_assets slice directly without converting to Vec<String>
     tm.preload_with_loading_screen(&all_assets, Some(loading_options)).await;
 
+    // Pick who is playing, their bankroll and table come from the saved profile
+    let store = ProfileStore::open_default();
+    let mut profile = pick_profile(&store).await;
+
+    // --join <host:port> sits down at a table on the network instead, the server keeps the chips there
+    if let Some(addr) = arg_value("--join") {
+        network_table(&addr, &profile.name, &profile.preferences.keys, &tm).await;
+        return;
+    }
+
+    // Pick a table in the lobby, its limits are kept with the profile's rules for next time
+    profile.preferences.rules.limits = pick_table(&profile).await;
+    let _ = store.save(&profile);
+
+    // Create the game with the player's table rules, it owns the shoe the cards are dealt from.
+    // Starting with --seed <number> deals the same shoe again, so a reported hand can be reproduced.
+    // The replay screen shows the seed of every round
+    let seed = seed_from_args().unwrap_or_else(|| miniquad::date::now() as u64);
+    let rules = profile.preferences.rules.clone();
+    let mut game = BlackjackGame::new(rules, profile.bankroll, seed);
+    // Who sits where comes from the profile, the computer players fill the other seats
+    let mut seat_players = profile.preferences.seats.clone();
+    if !valid_seats(&seat_players) {
+        seat_players = vec![SeatPlayer::Human];
+    }
+    let (mut you, mut bots) = seat_table(&mut game, &seat_players, profile.bankroll, seed);
+    // Record of every round played this session, for the replay screen
+    let mut rounds: Vec<RoundRecord> = Vec::new();
+    // Every settled round is appended to the hand history, --history <path> puts it somewhere else
+    let history = match arg_value("--history") {
+        Some(path) => HistoryWriter::new(path),
+        None => HistoryWriter::open_default(&profile.name),
+    };
+    // This session's part of the hand history, for the stats screen
+    let mut session: Vec<HistoryEntry> = Vec::new();
+    let session_start = profile.bankroll;
+
+    // Counts every card the player sees, the count trainer quizzes them on it
+    let system = system_by_name(&profile.preferences.counting_system).unwrap_or_else(|| Box::new(HiLo));
+    let mut counter = CardCounter::new(system, game.shoe().decks());
+    let mut quiz_score = QuizScore::default();
+    let mut quiz_pending = false;
+
     // Continue with the rest of the game setup
-    let mut show = "assets/backcard.png";
     let mut end_game = MessageBox::confirm("Out Of Chips", "You have run out of chips. Do you want to play again?");
+    // Rebuilt with the right wording each time the dealer shows an ace
+    let mut insurance_box = MessageBox::confirm("Insurance", "");
+    // Early surrender against a ten, asked before the dealer peeks
+    let mut surrender_box = MessageBox::confirm("Early Surrender", "The dealer shows a ten.\nSurrender half your bet before the peek?");
     let lblchips = Label::new("chips \n 500", 425.0, 525.0, 30);
-    let lblplayer = Label::new("0", 450.0, 275.0, 30);
+    let lblplayer = Label::new("", 20.0, 195.0, 22);
     let lbldealer = Label::new("0", 450.0, 100.0, 30);
     let lblwin = Label::new("", 450.0, 190.0, 30);
+    let lblshoe = Label::new(shoe_text(game.shoe()), 800.0, 50.0, 25);
+    let lblrules = Label::new(game.rules().to_string(), 100.0, 600.0, 25);
+    let lblprofile = Label::new(profile_text(&profile), 100.0, 650.0, 25);
+    let lblhint = Label::new("", 20.0, 415.0, 25);
+    let lblcheck = Label::new("", 450.0, 470.0, 22);
+    let lblodds = Label::new("", 620.0, 615.0, 20);
+    let lbltrainer = Label::new(trainer_text(&counter, &quiz_score, profile.preferences.count_trainer), 100.0, 695.0, 25);
     let mut start = TextButton::new(500.0, 400.0, 100.0, 50.0, "Start".to_string(), BLUE, GREEN, 30);
     let mut rand_card = TextButton::new(400.0, 400.0, 100.0, 50.0, "Hit".to_string(), BLUE, GREEN, 30);
     let mut stand = TextButton::new(300.0, 400.0, 100.0, 50.0, "Stand".to_string(), BLUE, GREEN, 30);
     let mut reset = TextButton::new(200.0, 400.0, 100.0, 50.0, "reset".to_string(), BLUE, GREEN, 30);
+    let mut double = TextButton::new(600.0, 400.0, 100.0, 50.0, "Double".to_string(), BLUE, GREEN, 30);
+    let mut split = TextButton::new(700.0, 400.0, 100.0, 50.0, "Split".to_string(), BLUE, GREEN, 30);
+    let mut surrender = TextButton::new(800.0, 400.0, 140.0, 50.0, "Surrender".to_string(), BLUE, GREEN, 30);
+    let mut hint = TextButton::new(810.0, 465.0, 130.0, 40.0, toggle_text("Hint", profile.preferences.show_hints), BLUE, GREEN, 25);
+    let mut check = TextButton::new(810.0, 515.0, 130.0, 40.0, toggle_text("Check", profile.preferences.check_plays), BLUE, GREEN, 25);
+    let mut odds = TextButton::new(810.0, 565.0, 130.0, 40.0, toggle_text("Odds", profile.preferences.show_odds), BLUE, GREEN, 25);
+    let mut answer = TextButton::new(100.0, 550.0, 100.0, 40.0, "Answer", BLUE, GREEN, 25);
+    let mut count = TextButton::new(205.0, 550.0, 110.0, 40.0, toggle_text("Count", profile.preferences.count_trainer), BLUE, GREEN, 25);
+    let mut system = TextButton::new(320.0, 550.0, 100.0, 40.0, counter.system().name(), BLUE, GREEN, 25);
+    let mut replay = TextButton::new(810.0, 140.0, 130.0, 40.0, "Replay", BLUE, GREEN, 25);
+    let mut stats = TextButton::new(810.0, 190.0, 130.0, 40.0, "Stats", BLUE, GREEN, 25);
+    let mut seats = TextButton::new(810.0, 240.0, 130.0, 40.0, "Seats", BLUE, GREEN, 25);
+    let mut deal_speed = TextButton::new(810.0, 615.0, 130.0, 40.0, deal_speed_text(profile.preferences.deal_speed), BLUE, GREEN, 22);
+    let mut clear = TextButton::new(545.0, 550.0, 70.0, 32.0, "Clear", BLUE, GREEN, 20);
+    let mut rebet = TextButton::new(620.0, 550.0, 75.0, 32.0, "Rebet", BLUE, GREEN, 20);
+    let mut rebet_double = TextButton::new(700.0, 550.0, 100.0, 32.0, "Rebet x2", BLUE, GREEN, 20);
+    // Chips to click a bet together, the bet box still takes an exact amount
+    let mut chip_rack = ChipRack::new(565.0, 520.0, 20.0);
+    let mut bet_stack = ChipStack::new(55.0, 520.0, 22.0);
     let mut txtbet = TextInput::new(100.0, 500.0, 300.0, 40.0, 25.0);
+    // Side bets go in their own boxes above the bet, left blank there is no side bet
+    let mut txtpairs = TextInput::new(100.0, 457.0, 145.0, 35.0, 20.0);
+    let mut txt21plus3 = TextInput::new(255.0, 457.0, 145.0, 35.0, 20.0);
 
     txtbet.with_colors(WHITE, RED, BLACK, WHITE);
     txtbet.set_prompt("Enter Bet Here");
+    txtpairs.with_colors(WHITE, RED, BLACK, WHITE);
+    txtpairs.set_prompt("Perfect Pairs");
+    txt21plus3.with_colors(WHITE, RED, BLACK, WHITE);
+    txt21plus3.set_prompt("21+3");
+    if profile.preferences.last_bet > 0 {
+        txtbet.set_text(profile.preferences.last_bet.to_string());
+    }
     rand_card.with_round(15.0);
     rand_card.with_border(RED, 5.0);
     start.with_round(15.0);
@@ -80,175 +193,460 @@ async fn main() {
     stand.with_border(RED, 5.0);
     reset.with_round(15.0);
     reset.with_border(RED, 5.0);
-    rand_card.enabled = false;
-    stand.enabled = false;
-    reset.enabled = false;
-
-    let pcard5 = StillImage::new(show, 75.0, 150.0, 345.0, 200.0, true, 1.0).await;
-    let pcard4 = StillImage::new(show, 75.0, 150.0, 290.0, 200.0, true, 1.0).await;
-    let pcard3 = StillImage::new(show, 75.0, 150.0, 235.0, 200.0, true, 1.0).await;
-    let pcard2 = StillImage::new(show, 75.0, 150.0, 180.0, 200.0, true, 1.0).await;
-    let pcard1 = StillImage::new(show, 75.0, 150.0, 125.0, 200.0, true, 1.0).await;
-    let dcard1 = StillImage::new(show, 75.0, 150.0, 125.0, 25.0, true, 1.0).await;
-    let dcard2 = StillImage::new(show, 75.0, 150.0, 180.0, 25.0, true, 1.0).await;
-    let dcard3 = StillImage::new(show, 75.0, 150.0, 235.0, 25.0, true, 1.0).await;
-    let dcard4 = StillImage::new(show, 75.0, 150.0, 290.0, 25.0, true, 1.0).await;
-    let dcard5 = StillImage::new(show, 75.0, 150.0, 345.0, 25.0, true, 1.0).await;
-    let mut pvalue = 0;
-    let mut dvalue = 0;
-    let mut turn = 3;
-    let mut dturn = 2;
-    let mut chips = 500;
-    let mut bet = 0;
-    let mut prebet = 0;
-
-    let mut labels: Vec<Label> = vec![lblchips, lblplayer, lbldealer, lblwin];
-
-    let mut images: Vec<StillImage> = vec![pcard1, pcard2, pcard3, pcard4, pcard5, dcard1, dcard2, dcard3, dcard4, dcard5];
-
-    rand::srand(miniquad::date::now() as u64);
+    double.with_round(15.0);
+    double.with_border(RED, 5.0);
+    split.with_round(15.0);
+    split.with_border(RED, 5.0);
+    surrender.with_round(15.0);
+    surrender.with_border(RED, 5.0);
+    hint.with_round(15.0);
+    hint.with_border(RED, 5.0);
+    check.with_round(15.0);
+    check.with_border(RED, 5.0);
+    odds.with_round(15.0);
+    odds.with_border(RED, 5.0);
+    answer.with_round(15.0);
+    answer.with_border(RED, 5.0);
+    count.with_round(15.0);
+    count.with_border(RED, 5.0);
+    system.with_round(15.0);
+    system.with_border(RED, 5.0);
+    replay.with_round(15.0);
+    replay.with_border(RED, 5.0);
+    stats.with_round(15.0);
+    stats.with_border(RED, 5.0);
+    seats.with_round(15.0);
+    seats.with_border(RED, 5.0);
+    deal_speed.with_round(15.0);
+    deal_speed.with_border(RED, 5.0);
+    clear.with_round(15.0);
+    clear.with_border(RED, 5.0);
+    rebet.with_round(15.0);
+    rebet.with_border(RED, 5.0);
+    rebet_double.with_round(15.0);
+    rebet_double.with_border(RED, 5.0);
+    // Keys from the profile, one action each. Start and Reset share the deal key as
+    // they are never enabled together. Only the confirm key works while typing in a box
+    let keys = &profile.preferences.keys;
+    bind_key(&mut rand_card, &keys.hit);
+    bind_key(&mut stand, &keys.stand);
+    bind_key(&mut double, &keys.double);
+    bind_key(&mut split, &keys.split);
+    bind_key(&mut surrender, &keys.surrender);
+    bind_key(&mut rebet, &keys.rebet);
+    bind_key(&mut start, &keys.deal);
+    bind_key(&mut reset, &keys.deal);
+    bind_key(&mut start, &keys.confirm);
+    bind_key(&mut answer, &keys.confirm);
+    let confirm_key = key_code(&keys.confirm);
+
+    let mut labels: Vec<Label> = vec![lblchips, lblplayer, lbldealer, lblwin, lblshoe, lblrules, lblprofile, lblhint, lblcheck, lblodds, lbltrainer];
+
+    // The dealer's cards stop short of the shoe label on the right
+    let mut dealer_view = HandView::new(125.0, 25.0, 650.0, 75.0, 150.0);
+
+    // A spot on the table for every seat, show_table fills them from the game
+    let mut seat_views = make_seat_views(seat_players.len());
+    deal_from_shoe(&mut dealer_view, &mut seat_views, profile.preferences.deal_speed);
+
+    show_table(&game, you, &bots, &mut seat_views, &mut dealer_view, &mut labels, &tm).await;
+
+    // Set when the table needs redrawing from the game, kept across frames so dialogs can set it too
+    let mut changed = false;
+    // Stops a settled round being added to the profile twice
+    let mut round_saved = false;
+    // Everything the game reports, the card counter goes through it before the table is redrawn
+    let mut events: Vec<GameEvent> = Vec::new();
+    // When the table last changed, computer players wait a moment after it before they act
+    let mut last_change = get_time();
+    // Odds being worked out for the table as it is now, see start_odds
+    let mut odds_job: Option<Receiver<Option<(DealerOdds, PlayerEv)>>> = None;
     loop {
         use_virtual_resolution(1024.0, 768.0);
         clear_background(DARKGREEN);
-        if start.click() {
-            prebet = chips;
-            labels[3].set_text(&format!(""));
-            let bet_amount = txtbet.get_text();
-            if let Ok(amount) = bet_amount.trim().parse::<i32>() {
-                if chips < amount && amount > 0 {
-                    labels[3].set_text(&format!("Not Enough Chips"));
-                } else {
-                    bet = amount;
-                    if bet <= 0 {
-                        labels[3].set_text(&format!("Invalid Bet"));
-                    } else {
-                        chips -= bet;
-                        labels[0].set_text(&format!("Chips:\n {}", chips));
-                        stand.enabled = true;
-                        rand_card.enabled = true;
-                        show = deck.choose().unwrap();
-
-                        images[0].set_preload(tm.get_preload(show).unwrap());
-                        deck.retain(|&x| x != show);
-                        pvalue = playervalue(pvalue, show);
-                        show = deck.choose().unwrap();
-                        images[1].set_preload(tm.get_preload(show).unwrap());
-                        deck.retain(|&x| x != show);
-                        pvalue = playervalue(pvalue, show);
-
-                        show = deck.choose().unwrap();
-                        images[5].set_preload(tm.get_preload(show).unwrap());
-                        deck.retain(|&x| x != show);
-                        dvalue = dealervalue(dvalue, show);
-                        start.enabled = false;
-                        labels[2].set_text(&format!("Dealer value:\n {}", dvalue));
-                        labels[1].set_text(&format!("Player value:\n {}", pvalue));
-                        println!("your bet is {}", bet);
-                    }
-                }
-            } else {
-                labels[3].set_text(&format!("Invalid bet"));
-            }
-        }
-        if rand_card.click() {
-            show = deck.choose().unwrap();
-            if turn == 3 {
-                images[2].set_preload(tm.get_preload(show).unwrap());
-
-                deck.retain(|&x| x != show);
 
-                pvalue = playervalue(pvalue, show);
+        // Buttons follow what the game allows right now
+        // A count quiz has to be answered before the next round
+        // The play buttons only work on the player's own turn
+        // Nothing can be clicked while cards are still moving
+        let animating = dealer_view.is_animating() || seat_views.iter().any(|view| view.is_animating());
+        let your_turn = game.phase() == Phase::PlayerTurn && game.active_seat() == Some(you);
+        start.enabled = game.phase() == Phase::Betting && !quiz_pending;
+        rand_card.enabled = your_turn && game.can_hit();
+        stand.enabled = your_turn;
+        reset.enabled = game.phase() == Phase::Settlement && !quiz_pending;
+        answer.enabled = quiz_pending;
+        double.enabled = your_turn && game.can_double();
+        split.enabled = your_turn && game.can_split();
+        surrender.enabled = your_turn && game.can_surrender();
+        replay.enabled = !rounds.is_empty() && matches!(game.phase(), Phase::Betting | Phase::Settlement);
 
-                turn = 4;
-            } else if turn == 4 {
-                images[3].set_preload(tm.get_preload(show).unwrap());
-                deck.retain(|&x| x != show);
+        stats.enabled = matches!(game.phase(), Phase::Betting | Phase::Settlement);
+        seats.enabled = game.phase() == Phase::Betting;
+        chip_rack.enabled = start.enabled;
+        clear.enabled = start.enabled;
+        rebet.enabled = start.enabled && profile.preferences.last_bet > 0;
+        rebet_double.enabled = rebet.enabled;
+        // The keys would be typed into the box as well
+        let typing = [&txtbet, &txtpairs, &txt21plus3].iter().any(|input| input.is_active());
+        for button in [&mut start, &mut rand_card, &mut stand, &mut reset, &mut double, &mut split, &mut surrender, &mut answer, &mut rebet] {
+            button.keys_enabled = !typing;
+        }
+        let confirmed = typing && confirm_key.is_some_and(is_key_pressed);
+        if animating {
+            for button in [&mut start, &mut rand_card, &mut stand, &mut reset, &mut double, &mut split, &mut surrender, &mut answer] {
+                button.enabled = false;
+            }
+            for button in [&mut replay, &mut stats, &mut seats, &mut clear, &mut rebet, &mut rebet_double] {
+                button.enabled = false;
+            }
+            chip_rack.enabled = false;
+        }
 
-                pvalue = playervalue(pvalue, show);
+        if replay.click() {
+            replay_rounds(&rounds, &tm).await;
+        }
+        if stats.click() {
+            stats_screen(&session, session_start, &profile, history.jsonl_path()).await;
+        }
+        if seats.click() {
+            if let Some(players) = seat_setup(&seat_players).await {
+                // The player keeps their chips wherever they sit
+                let bankroll = game.seats()[you].bankroll();
+                (you, bots) = seat_table(&mut game, &players, bankroll, seed);
+                seat_views = make_seat_views(players.len());
+                deal_from_shoe(&mut dealer_view, &mut seat_views, profile.preferences.deal_speed);
+                seat_players = players.clone();
+                profile.preferences.seats = players;
+                save_profile(&store, &profile, &mut labels[6]);
+                changed = true;
+            }
+        }
 
-                turn = 5;
-            } else if turn == 5 {
-                images[4].set_preload(tm.get_preload(show).unwrap());
-                deck.retain(|&x| x != show);
+        // Chips change the amount in the bet box, never past what the player has or the table maximum
+        let most = game.seats()[you].bankroll().min(game.rules().limits.max_bet);
+        let typed = bet_amount(&txtbet);
+        let chip_bet = match chip_rack.click() {
+            Some(ChipClick::Add(value)) if typed + value <= most => Some(typed + value),
+            Some(ChipClick::Remove(value)) => Some(typed.saturating_sub(value)),
+            _ => None,
+        };
+        if let Some(amount) = chip_bet {
+            txtbet.set_text(if amount > 0 { amount.to_string() } else { String::new() });
+        }
+        if clear.click() {
+            txtbet.set_text("");
+            txtpairs.set_text("");
+            txt21plus3.set_text("");
+        }
+        // Rebets stop at what the player has left or the table maximum, like the chips
+        if rebet.click() {
+            txtbet.set_text(profile.preferences.last_bet.min(most).to_string());
+        }
+        if rebet_double.click() {
+            txtbet.set_text((profile.preferences.last_bet * 2).min(most).to_string());
+        }
+        // The stack shows the bet being made, then the chips in play once the cards are out
+        if game.phase() == Phase::Betting {
+            bet_stack.set_amount(bet_amount(&txtbet));
+        } else {
+            bet_stack.set_amount(game.seats()[you].hands().iter().map(|hand| hand.bet).sum());
+        }
 
-                pvalue = playervalue(pvalue, show);
+        if start.click() || (confirmed && start.enabled) {
+            labels[3].set_text("");
+            labels[8].set_text("");
+            let side_bets = [
+                (SideBet::PerfectPairs, side_bet_amount(&txtpairs)),
+                (SideBet::TwentyOnePlusThree, side_bet_amount(&txt21plus3)),
+            ];
+            let chips = game.seats()[you].bankroll() + game.seats()[you].bet();
+            match game.rules().limits.parse_bet(&txtbet.get_text(), chips) {
+                Ok(_) if side_bets.iter().any(|(_, amount)| amount.is_none()) => {
+                    labels[3].set_text("Invalid side bet");
+                }
+                Ok(amount) => {
+                    // The computer players bet once the player's bets are down
+                    let mut placed = game.place_bet_at(you, amount);
+                    for (bet, side) in side_bets {
+                        placed = placed.and_then(|mut placed| {
+                            placed.extend(game.place_side_bet_at(you, bet, side.unwrap_or(0))?);
+                            Ok(placed)
+                        });
+                    }
+                    let placed = placed.map(|placed| {
+                        events.extend(placed);
+                        place_ai_bets(&mut game, &bots, &mut events);
+                    });
+                    match placed.and_then(|_| game.deal()) {
+                        Ok(dealt) => {
+                            events.extend(dealt);
+                            profile.preferences.last_bet = amount;
+                            labels[3].set_text(side_bet_text(&game.seats()[you]).trim_start());
+                            // The play keys would be typed into a box that kept the focus
+                            for input in [&mut txtbet, &mut txtpairs, &mut txt21plus3] {
+                                input.set_active(false);
+                            }
+                        }
+                        Err(err) => {
+                            // Nothing stays on the table from a bet that did not go through
+                            let _ = game.clear_bet(you);
+                            labels[3].set_text(err.to_string());
+                        }
+                    }
+                }
+                Err(err) => {
+                    labels[3].set_text(err.to_string());
+                }
             }
-            if pvalue > 21 {
-                rand_card.enabled = false;
-                stand.enabled = false;
-                reset.enabled = true;
-                prebet = chips;
-                labels[3].set_text(&format!("You lose"));
+            changed = true;
+        }
+        // The advice is taken before the play so it can be checked against what the player did
+        let advice = advise(&game);
+        let mut played = None;
+        if rand_card.click() {
+            if let Ok(dealt) = game.hit() {
+                events.extend(dealt);
+                played = Some(Play::Hit);
             }
-            labels[1].set_text(&format!("Player value:\n {}", pvalue));
-            println!("your bet is {}", bet);
         }
-
         if stand.click() {
-            stand.enabled = false;
-            rand_card.enabled = false;
-
-            while dvalue < 18 {
-                show = deck.choose().unwrap();
-                deck.retain(|&x| x != show);
-                if dturn == 2 {
-                    images[6].set_preload(tm.get_preload(show).unwrap());
-                    dturn = 3;
-                } else if dturn == 3 {
-                    images[7].set_preload(tm.get_preload(show).unwrap());
-                    dturn = 4;
-                } else if dturn == 4 {
-                    images[8].set_preload(tm.get_preload(show).unwrap());
-                    dturn = 5;
-                } else if dturn == 5 {
-                    images[9].set_preload(tm.get_preload(show).unwrap());
+            if let Ok(dealt) = game.stand() {
+                events.extend(dealt);
+                played = Some(Play::Stand);
+            }
+        }
+        if double.click() {
+            if let Ok(dealt) = game.double() {
+                events.extend(dealt);
+                played = Some(Play::Double);
+            }
+        }
+        if split.click() {
+            if let Ok(dealt) = game.split() {
+                events.extend(dealt);
+                played = Some(Play::Split);
+            }
+        }
+        if surrender.click() {
+            if let Ok(dealt) = game.surrender() {
+                events.extend(dealt);
+                played = Some(Play::Surrender);
+            }
+        }
+        if let Some(play) = played {
+            if profile.preferences.check_plays {
+                labels[8].set_text(check_text(advice, play));
+            }
+            changed = true;
+        }
+        // Computer players take their turn once they have had a moment to think
+        if let Some(seat) = game.active_seat().filter(|seat| *seat != you) {
+            if get_time() - last_change >= AI_DELAY && !animating {
+                if let Some(bot) = &bots[seat] {
+                    events.extend(ai_turn(&mut game, bot));
                 }
-                dvalue = dealervalue(dvalue, show);
+                changed = true;
             }
-            labels[2].set_text(&format!("Dealer value:\n {}", dvalue));
-
-            if pwincheck(pvalue, dvalue) == true {
-                chips += bet * 2;
-                labels[0].set_text(&format!("Chips:\n {}", chips));
-                labels[3].set_text(&format!("You Win"));
-            } else if dwincheck(pvalue, dvalue) == true {
-                prebet = chips;
+        }
+        if deal_speed.click() {
+            // Round the list of speeds, from wherever the profile's speed is
+            let next = DEAL_SPEEDS.iter().position(|(_, speed)| *speed == profile.preferences.deal_speed).map_or(0, |i| (i + 1) % DEAL_SPEEDS.len());
+            profile.preferences.deal_speed = DEAL_SPEEDS[next].1;
+            deal_speed.set_text(deal_speed_text(profile.preferences.deal_speed));
+            deal_from_shoe(&mut dealer_view, &mut seat_views, profile.preferences.deal_speed);
+            save_profile(&store, &profile, &mut labels[6]);
+        }
+        if hint.click() {
+            profile.preferences.show_hints = !profile.preferences.show_hints;
+            hint.set_text(toggle_text("Hint", profile.preferences.show_hints));
+            save_profile(&store, &profile, &mut labels[6]);
+            changed = true;
+        }
+        if check.click() {
+            profile.preferences.check_plays = !profile.preferences.check_plays;
+            check.set_text(toggle_text("Check", profile.preferences.check_plays));
+            save_profile(&store, &profile, &mut labels[6]);
+            labels[8].set_text("");
+            changed = true;
+        }
+        if odds.click() {
+            profile.preferences.show_odds = !profile.preferences.show_odds;
+            odds.set_text(toggle_text("Odds", profile.preferences.show_odds));
+            save_profile(&store, &profile, &mut labels[6]);
+            changed = true;
+        }
+        if count.click() {
+            profile.preferences.count_trainer = !profile.preferences.count_trainer;
+            count.set_text(toggle_text("Count", profile.preferences.count_trainer));
+            quiz_pending = false;
+            save_profile(&store, &profile, &mut labels[6]);
+            labels[10].set_text(trainer_text(&counter, &quiz_score, profile.preferences.count_trainer));
+        }
+        if system.click() {
+            // Step through the systems, the cards already seen are recounted with the new one
+            let current = SYSTEM_NAMES.iter().position(|name| *name == counter.system().name()).unwrap_or(0);
+            let name = SYSTEM_NAMES[(current + 1) % SYSTEM_NAMES.len()];
+            counter.set_system(system_by_name(name).unwrap());
+            system.set_text(name);
+            profile.preferences.counting_system = name.to_string();
+            save_profile(&store, &profile, &mut labels[6]);
+            labels[10].set_text(trainer_text(&counter, &quiz_score, profile.preferences.count_trainer));
+        }
+        if answer.click() || (confirmed && answer.enabled) {
+            match txtbet.get_text().trim().parse::<i32>() {
+                Ok(guess) => {
+                    let running = counter.running_count();
+                    quiz_score.record(guess == running);
+                    let mut text = if guess == running {
+                        format!("Right, the count is {}", running)
+                    } else {
+                        format!("The count is {}, not {}", running, guess)
+                    };
+                    if counter.system().is_balanced() {
+                        text.push_str(&format!("\n(true count {:.1})", counter.true_count(game.shoe().decks_remaining())));
+                    }
+                    labels[3].set_text(text);
+                    labels[10].set_text(trainer_text(&counter, &quiz_score, profile.preferences.count_trainer));
+                    txtbet.set_text(bet_text(&profile));
+                    quiz_pending = false;
+                }
+                Err(_) => {
+                    labels[3].set_text("Type the running count\nin the bet box");
+                }
+            }
+        }
+        // Not enough chips for insurance or even money, so go straight to the peek
+        if game.phase() == Phase::Insurance && game.active_seat() == Some(you) && game.max_insurance() == 0 && !game.can_take_even_money() {
+            if let Ok(dealt) = game.decline_insurance() {
+                events.extend(dealt);
+            }
+            changed = true;
+        }
+        // Once every hand is played the dealer takes their turn a step at a time: the hole card,
+        // then each card they draw, a moment after the last card landed. The round is settled at the end
+        let dealer_ready = get_time() - last_change >= profile.preferences.dealer_delay as f64;
+        if game.phase() == Phase::DealerTurn && dealer_ready && !animating {
+            if let Ok(dealt) = game.dealer_step() {
+                events.extend(dealt);
+            }
+            changed = true;
+        }
+        if reset.click() {
+            if let Ok(dealt) = game.next_round() {
+                events.extend(dealt);
+            }
+            txtbet.set_text(bet_text(&profile));
+            labels[3].set_text("");
+            round_saved = false;
+            changed = true;
+        }
 
-                labels[3].set_text(&format!("You lose"));
+        if changed {
+            counter.observe(&events);
+            for bot in bots.iter_mut().flatten() {
+                bot.observe(&events);
+            }
+            // Shuffles used to happen without a word, the count starts over so the player needs to know
+            if events.contains(&GameEvent::Shuffled) {
+                labels[3].set_text("The shoe was shuffled");
+            }
+            events.clear();
+            show_table(&game, you, &bots, &mut seat_views, &mut dealer_view, &mut labels, &tm).await;
+            labels[7].set_text(hint_text(&game, you, profile.preferences.show_hints));
+            // A job for an older table is dropped, its answer goes nowhere
+            odds_job = start_odds(&game, you, profile.preferences.show_odds);
+            labels[9].set_text(if odds_job.is_some() { "Odds ..." } else { "" });
+            if game.phase() == Phase::Settlement && !round_saved {
+                // Autosave once per round, as soon as it is settled
+                profile.record_round(&game, you);
+                save_profile(&store, &profile, &mut labels[6]);
+                round_saved = true;
+                if let Some(record) = game.round_record() {
+                    rounds.push(record.clone());
+                }
+                if let Some(entry) = HistoryEntry::from_game(&game, you, miniquad::date::now() as u64) {
+                    if let Err(err) = history.append(&entry) {
+                        labels[6].set_text(format!("Could not write the hand history: {}", err));
+                    }
+                    session.push(entry);
+                }
+                labels[3].set_text(outcome_text(&game, you));
+                // Side bets are made again for every round
+                txtpairs.set_text("");
+                txt21plus3.set_text("");
+                // Chips left under the table minimum cannot be bet either
+                if game.seats()[you].bankroll() < game.rules().limits.min_bet {
+                    end_game.show();
+                    labels[3].set_text("No More Chips");
+                } else if profile.preferences.count_trainer && macroquad::rand::gen_range(0, 3) == 0 {
+                    // Every so often the trainer asks for the count before the next round
+                    quiz_pending = true;
+                    labels[3].set_text(format!("{}\nWhat is the running count?", outcome_text(&game, you)));
+                    txtbet.set_text("");
+                }
+            }
+            changed = false;
+            last_change = get_time();
+        }
+        if let Some(job) = &odds_job {
+            match job.try_recv() {
+                Ok(odds) => {
+                    labels[9].set_text(odds.map_or(String::new(), |(dealer, ev)| odds_text(&dealer, &ev)));
+                    odds_job = None;
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => odds_job = None,
+            }
+        }
+        // The player is asked about insurance once the cards are down
+        let dealing = dealer_view.is_animating() || seat_views.iter().any(|view| view.is_animating());
+        if game.phase() == Phase::Insurance && game.active_seat() == Some(you) && !insurance_box.is_visible() && !dealing {
+            if game.can_take_even_money() {
+                insurance_box = MessageBox::confirm("Even Money", "You have blackjack and the dealer shows an ace.\nTake even money?");
+                insurance_box.show();
             } else {
-                chips += bet;
-                labels[0].set_text(&format!("Chips:\n {}", chips));
-                labels[3].set_text(&format!("You Draw"));
+                let message = format!("The dealer shows an ace.\nTake insurance for {}?", game.max_insurance());
+                insurance_box = MessageBox::confirm("Insurance", message);
+                insurance_box.show();
             }
-            println!("your bet is {}", bet);
-            reset.enabled = true;
         }
-        if reset.click() {
-            for i in 0..10 {
-                        images[i].set_preload(tm.get_preload("assets/backcard.png").unwrap());
+
+        if game.phase() == Phase::Dealing && game.active_seat() == Some(you) && !surrender_box.is_visible() && !dealing {
+            surrender_box.show();
+        }
+        if let Some(result) = surrender_box.draw() {
+            let result = match result {
+                MessageBoxResult::ButtonPressed(0) => game.surrender(),
+                _ => game.decline_surrender(),
+            };
+            if let Ok(dealt) = result {
+                events.extend(dealt);
+            }
+            changed = true;
+        }
+
+        if let Some(result) = insurance_box.draw() {
+            match result {
+                MessageBoxResult::ButtonPressed(0) => {
+                    // "Yes" button pressed
+                    let result = if game.can_take_even_money() {
+                        game.take_even_money()
+                    } else {
+                        game.take_insurance(game.max_insurance())
+                    };
+                    if let Ok(dealt) = result {
+                        events.extend(dealt);
+                    }
+                }
+                _ => {
+                    // "No" or closed
+                    if let Ok(dealt) = game.decline_insurance() {
+                        events.extend(dealt);
                     }
-            pvalue = 0;
-            dvalue = 0;
-            turn = 3;
-            dturn = 2;
-            bet = 0;
-            start.enabled = true;
-            rand_card.enabled = false;
-            stand.enabled = false;
-            reset.enabled = false;
-            labels[1].set_text(&format!("Player value:\n {}", pvalue));
-            labels[2].set_text(&format!("Dealer value:\n {}", dvalue));
-            txtbet.set_text(&format!(""));
-            labels[3].set_text(&format!(""));
-            if deck.len() <=10 {
-            deck = deck_reset();
-            }
-            
-        }
-        if chips == 0 && bet != prebet {
-            end_game.show();
-            prebet = bet;
-            labels[3].set_text(&format!("No More Chips"));
+                }
+            }
+            changed = true;
         }
 
         if let Some(result) = end_game.draw() {
@@ -256,27 +654,16 @@ async fn main() {
             match result {
                 MessageBoxResult::ButtonPressed(0) => {
                     // "Yes" button pressed
-                    for i in 0..10 {
-                        images[i].set_preload(tm.get_preload("assets/backcard.png").unwrap());
+                    profile.rebuy();
+                    save_profile(&store, &profile, &mut labels[6]);
+                    let _ = game.set_bankroll_at(you, profile.bankroll);
+                    if let Ok(dealt) = game.next_round() {
+                        events.extend(dealt);
                     }
-         
-                    pvalue = 0;
-                    dvalue = 0;
-                    turn = 3;
-                    dturn = 2;
-                    bet = 0;
-                    start.enabled = true;
-                    rand_card.enabled = false;
-                    stand.enabled = false;
-                    reset.enabled = false;
-                    labels[1].set_text(&format!("Player value:\n {}", pvalue));
-                    labels[2].set_text(&format!("Dealer value:\n {}", dvalue));
-                    labels[3].set_text(&format!(""));
-                    chips = 500;
-                    labels[0].set_text(&format!("Chips:\n {}", chips));
-                    reset.enabled = false;
-                    txtbet.set_text(&format!(""));
-                    
+                    round_saved = false;
+                    labels[3].set_text("");
+                    txtbet.set_text(bet_text(&profile));
+                    changed = true;
                 }
                 MessageBoxResult::ButtonPressed(1) => {
                     // "No" button pressed
@@ -301,133 +688,1208 @@ async fn main() {
                 }
             }
         }
-        for image in &images {
-            image.draw();
+        dealer_view.draw();
+        for view in &seat_views {
+            view.draw();
+        }
+        // Box round the hand being played, or the first hand of the seat deciding on insurance
+        if let Some(seat) = game.active_seat() {
+            if let Some(area) = seat_views[seat].hand_bounds(game.active_hand().unwrap_or(0)) {
+                draw_rectangle_lines(area.x - 5.0, area.y - 5.0, area.w + 10.0, area.h + 10.0, 4.0, YELLOW);
+            }
         }
         for label in &labels {
             label.draw();
         }
 
+        bet_stack.draw();
         txtbet.draw();
+        txtpairs.draw();
+        txt21plus3.draw();
+        insurance_box.draw();
+        surrender_box.draw();
         end_game.draw();
 
         next_frame().await;
     }
 }
 
-fn playervalue(mut pvalue: i32, show: &str) -> i32 {
-    if show.contains("ace") {
-        pvalue += 11;
-        if pvalue > 21 {
-            pvalue -= 10;
+// Lets the key named in the profile press a button, see KeyBindings
+fn bind_key(button: &mut TextButton, name: &str) {
+    if let Some(key) = key_code(name) {
+        button.activate_on_key(key);
+    }
+}
+
+// The key for a name in the key bindings, any case
+fn key_code(name: &str) -> Option<KeyCode> {
+    let key = match name.trim().to_ascii_uppercase().as_str() {
+        "A" => KeyCode::A,
+        "B" => KeyCode::B,
+        "C" => KeyCode::C,
+        "D" => KeyCode::D,
+        "E" => KeyCode::E,
+        "F" => KeyCode::F,
+        "G" => KeyCode::G,
+        "H" => KeyCode::H,
+        "I" => KeyCode::I,
+        "J" => KeyCode::J,
+        "K" => KeyCode::K,
+        "L" => KeyCode::L,
+        "M" => KeyCode::M,
+        "N" => KeyCode::N,
+        "O" => KeyCode::O,
+        "P" => KeyCode::P,
+        "Q" => KeyCode::Q,
+        "R" => KeyCode::R,
+        "S" => KeyCode::S,
+        "T" => KeyCode::T,
+        "U" => KeyCode::U,
+        "V" => KeyCode::V,
+        "W" => KeyCode::W,
+        "X" => KeyCode::X,
+        "Y" => KeyCode::Y,
+        "Z" => KeyCode::Z,
+        "0" => KeyCode::Key0,
+        "1" => KeyCode::Key1,
+        "2" => KeyCode::Key2,
+        "3" => KeyCode::Key3,
+        "4" => KeyCode::Key4,
+        "5" => KeyCode::Key5,
+        "6" => KeyCode::Key6,
+        "7" => KeyCode::Key7,
+        "8" => KeyCode::Key8,
+        "9" => KeyCode::Key9,
+        "SPACE" => KeyCode::Space,
+        "ENTER" => KeyCode::Enter,
+        "TAB" => KeyCode::Tab,
+        "BACKSPACE" => KeyCode::Backspace,
+        _ => return None,
+    };
+    Some(key)
+}
+
+// Makes the dealer's and the seats' cards come out of the shoe, `seconds` for each card
+fn deal_from_shoe(dealer_view: &mut HandView, seat_views: &mut [SeatView], seconds: f32) {
+    dealer_view.with_deal_from(SHOE_SPOT.0, SHOE_SPOT.1).set_deal_speed(seconds);
+    for view in seat_views {
+        view.set_deal(SHOE_SPOT.0, SHOE_SPOT.1, seconds);
+    }
+}
+
+// Text for the Deal button
+fn deal_speed_text(seconds: f32) -> String {
+    let name = DEAL_SPEEDS.iter().find(|(_, speed)| *speed == seconds).map_or("Custom", |(name, _)| name);
+    format!("Deal: {}", name)
+}
+
+// Left edge and width of a seat. A single seat keeps the usual spot,
+// more seats share the width of the table
+fn seat_area(seat: usize, seat_count: usize) -> (f32, f32) {
+    if seat_count <= 1 {
+        return (125.0, 800.0);
+    }
+    let width = 924.0 / seat_count as f32;
+    (50.0 + seat as f32 * width, width - 10.0)
+}
+
+// A spot for every seat, the cards get smaller as the table fills up
+fn make_seat_views(seat_count: usize) -> Vec<SeatView> {
+    let (card_width, card_height) = match seat_count {
+        0..=2 => (75.0, 150.0),
+        3..=4 => (60.0, 120.0),
+        _ => (45.0, 90.0),
+    };
+    (0..seat_count)
+        .map(|seat| {
+            let (x, width) = seat_area(seat, seat_count);
+            SeatView::new(x, 200.0, width, card_width, card_height)
+        })
+        .collect()
+}
+
+// Result shown after a hand's total and bet, blank until it is settled
+fn result_text(outcome: Option<Outcome>) -> &'static str {
+    match outcome {
+        Some(Outcome::Blackjack) | Some(Outcome::Win) => " Win",
+        Some(Outcome::Push) => " Push",
+        Some(Outcome::Lose) | Some(Outcome::Bust) => " Lose",
+        Some(Outcome::Surrender) => " Surrender",
+        None => "",
+    }
+}
+
+// Total, bet and result shown under a hand
+fn hand_text(player: &PlayerHand) -> String {
+    format!("{}  Bet {}{}", player.hand.best_total(), player.bet, result_text(player.outcome))
+}
+
+fn seat_name(bots: &[Option<AiPlayer>], seat: usize) -> String {
+    match &bots[seat] {
+        Some(bot) => format!("{} ({})", bot.name, bot.style),
+        None => "You".to_string(),
+    }
+}
+
+// Whose turn it is
+fn turn_text(game: &BlackjackGame, you: usize, bots: &[Option<AiPlayer>]) -> String {
+    match (game.phase(), game.active_seat()) {
+        (_, Some(seat)) if seat == you => "Your turn".to_string(),
+        (_, Some(seat)) => format!("{} is playing", seat_name(bots, seat)),
+        (Phase::DealerTurn, None) => "Dealer's turn".to_string(),
+        _ => String::new(),
+    }
+}
+
+// Puts the cards and totals from the game on the table
+async fn show_table(
+    game: &BlackjackGame,
+    you: usize,
+    bots: &[Option<AiPlayer>],
+    seat_views: &mut [SeatView],
+    dealer_view: &mut HandView,
+    labels: &mut [Label],
+    tm: &TextureManager,
+) {
+    let back = tm.get_preload(CARD_BACK).unwrap();
+
+    // Between rounds every seat and the dealer show two face down cards,
+    // during a round a seat that sat out shows nothing
+    for (seat, view) in seat_views.iter_mut().enumerate() {
+        let player = &game.seats()[seat];
+        let hands = if player.is_playing() {
+            player
+                .hands()
+                .iter()
+                .map(|hand| {
+                    let cards = hand.hand.cards().iter().map(|card| tm.get_preload(card.asset_path()).unwrap()).collect();
+                    (cards, hand_text(hand))
+                })
+                .collect()
+        } else if game.phase() == Phase::Betting {
+            vec![(vec![back.clone(), back.clone()], String::new())]
+        } else {
+            Vec::new()
+        };
+        view.set_hands(hands).await;
+        view.set_name(format!("{}  {}", seat_name(bots, seat), player.bankroll()));
+    }
+
+    // The hole card keeps showing the back until it is revealed
+    let mut dealer_cards = Vec::new();
+    for (i, card) in game.dealer().cards().iter().enumerate() {
+        if i == 1 && game.hole_card_hidden() {
+            dealer_cards.push(back.clone());
+        } else {
+            dealer_cards.push(tm.get_preload(card.asset_path()).unwrap());
+        }
+    }
+    if dealer_cards.is_empty() {
+        dealer_cards = vec![back.clone(), back.clone()];
+    }
+    dealer_view.set_cards(dealer_cards).await;
+
+    labels[0].set_text(format!("Chips:\n {}", game.seats()[you].bankroll()));
+    labels[1].set_text(turn_text(game, you, bots));
+    labels[2].set_text(format!("Dealer value:\n {}", game.dealer_showing().best_total()));
+    labels[4].set_text(shoe_text(game.shoe()));
+}
+
+// Message for the end of the round, for one seat
+fn outcome_text(game: &BlackjackGame, seat: usize) -> String {
+    let player = &game.seats()[seat];
+    // Split hands show their own results, so just sum them up here
+    if player.hands().len() > 1 {
+        let staked = player.total_staked() as i64;
+        let net = player.total_payout() as i64 - staked;
+        let text = match net {
+            n if n > 0 => format!("You Win {}", n),
+            n if n < 0 => format!("You lose {}", -n),
+            _ => "You Draw".to_string(),
+        };
+        return format!("{}{}", text, side_bet_text(player));
+    }
+    let Some(hand) = player.hands().first() else {
+        return String::new();
+    };
+    let side_payout: u32 = player.side_bets().iter().map(|stake| stake.payout).sum();
+    let text = match hand.outcome {
+        Some(Outcome::Blackjack) => "Blackjack!",
+        Some(Outcome::Win) => "You Win",
+        Some(Outcome::Push) => "You Draw",
+        Some(Outcome::Lose) | Some(Outcome::Bust) => "You lose",
+        Some(Outcome::Surrender) => "Surrendered",
+        None => "",
+    };
+    let mut text = text.to_string();
+    if hand.doubled {
+        text.push_str("\n(doubled)");
+    }
+    if hand.even_money {
+        text.push_str("\n(even money)");
+    }
+    if player.insurance() > 0 {
+        if player.total_payout() > hand.payout + side_payout {
+            text.push_str("\nInsurance pays");
+        } else {
+            text.push_str("\nInsurance lost");
+        }
+    }
+    text.push_str(&side_bet_text(player));
+    text
+}
+
+// A line for each of the seat's side bets once the cards are out, each one starting on a new line
+fn side_bet_text(player: &Seat) -> String {
+    player
+        .side_bets()
+        .iter()
+        .map(|stake| match stake.result {
+            Some(result) => format!("\n{}: {} pays {}", stake.bet, result, stake.payout),
+            None => format!("\n{} lost", stake.bet),
+        })
+        .collect()
+}
+
+// Chips in a side bet box, 0 when it is blank and None when it is not a number
+fn side_bet_amount(input: &TextInput) -> Option<u32> {
+    let text = input.get_text();
+    if text.trim().is_empty() {
+        Some(0)
+    } else {
+        text.trim().parse().ok()
+    }
+}
+
+// Profile screen shown before the table: a button for every saved player and a box to add a new one
+async fn pick_profile(store: &ProfileStore) -> Profile {
+    let profiles = store.list();
+    let title = Label::new("Choose a Profile", 350.0, 80.0, 40);
+    let mut message = Label::new("", 312.0, 700.0, 25);
+    // Eight players fit on the screen, the arrows turn the page when there are more
+    let pages = profiles.len().div_ceil(PROFILES_PER_PAGE).max(1);
+    let mut page = 0;
+    let mut buttons = profile_buttons(&profiles, page);
+    let mut page_label = Label::new(if pages > 1 { format!("Page 1 of {}", pages) } else { String::new() }, 455.0, 605.0, 25);
+    let mut prev = TextButton::new(232.0, 330.0, 60.0, 50.0, "<", BLUE, GREEN, 30);
+    let mut next = TextButton::new(732.0, 330.0, 60.0, 50.0, ">", BLUE, GREEN, 30);
+    for button in [&mut prev, &mut next] {
+        button.with_round(15.0);
+        button.with_border(RED, 5.0);
+        button.visuable = pages > 1;
+    }
+    let mut txtname = TextInput::new(312.0, 620.0, 280.0, 50.0, 25.0);
+    txtname.with_colors(WHITE, RED, BLACK, WHITE);
+    txtname.set_prompt("New player name");
+    let mut create = TextButton::new(602.0, 620.0, 110.0, 50.0, "New", BLUE, GREEN, 30);
+    create.with_round(15.0);
+    create.with_border(RED, 5.0);
+
+    loop {
+        use_virtual_resolution(1024.0, 768.0);
+        clear_background(DARKGREEN);
+
+        prev.enabled = page > 0;
+        next.enabled = page + 1 < pages;
+        let turn = match (prev.click(), next.click()) {
+            (true, _) => Some(page - 1),
+            (_, true) => Some(page + 1),
+            _ => None,
+        };
+        if let Some(turned) = turn {
+            page = turned;
+            buttons = profile_buttons(&profiles, page);
+            page_label.set_text(format!("Page {} of {}", page + 1, pages));
+        }
+        for (i, button) in buttons.iter().enumerate() {
+            if button.click() {
+                return profiles[page * PROFILES_PER_PAGE + i].clone();
+            }
+        }
+        if create.click() {
+            match store.create(&txtname.get_text()) {
+                Ok(profile) => return profile,
+                // Nowhere to save (the browser build has no files), the player still gets to play
+                Err(ProfileError::Io(_)) => return Profile::new(&txtname.get_text()),
+                Err(err) => {
+                    message.set_text(err.to_string());
+                }
+            }
+        }
+
+        title.draw();
+        txtname.draw();
+        message.draw();
+        page_label.draw();
+        next_frame().await;
+    }
+}
+
+// A button for each saved player on one page of the profile screen
+fn profile_buttons(profiles: &[Profile], page: usize) -> Vec<TextButton> {
+    let mut buttons = Vec::new();
+    for (i, saved) in profiles.iter().skip(page * PROFILES_PER_PAGE).take(PROFILES_PER_PAGE).enumerate() {
+        let text = format!("{}  ({} chips)", saved.name, saved.bankroll);
+        let mut button = TextButton::new(312.0, 120.0 + i as f32 * 60.0, 400.0, 50.0, text, BLUE, GREEN, 30);
+        button.with_round(15.0);
+        button.with_border(RED, 5.0);
+        buttons.push(button);
+    }
+    buttons
+}
+
+// The lobby, a button for each table. Tables with a minimum over the player's chips cannot be picked,
+// the cheapest one always can
+async fn pick_table(profile: &Profile) -> TableLimits {
+    let title = Label::new("Choose a Table", 360.0, 80.0, 40);
+    let about = Label::new("Spread is how many times the minimum bet the maximum is", 240.0, 120.0, 25);
+    let current = profile.preferences.rules.limits;
+    let mut buttons: Vec<TextButton> = Vec::new();
+    for (i, table) in LOBBY.iter().enumerate() {
+        let mut text = format!("{}  {}  spread {}", table.name, table.limits, table.limits.spread());
+        if table.limits == current {
+            text.push_str("  *");
+        }
+        let mut button = TextButton::new(262.0, 160.0 + i as f32 * 70.0, 500.0, 55.0, text, BLUE, GREEN, 28);
+        button.with_round(15.0);
+        button.with_border(RED, 5.0);
+        button.enabled = i == 0 || profile.bankroll >= table.limits.min_bet;
+        buttons.push(button);
+    }
+    let chips = Label::new(format!("{} has {} chips, * is the last table played", profile.name, profile.bankroll), 262.0, 480.0, 25);
+
+    loop {
+        use_virtual_resolution(1024.0, 768.0);
+        clear_background(DARKGREEN);
+
+        for (i, button) in buttons.iter().enumerate() {
+            if button.click() {
+                return LOBBY[i].limits;
+            }
+        }
+
+        title.draw();
+        about.draw();
+        chips.draw();
+        next_frame().await;
+    }
+}
+
+// Basic strategy play for the hand being played, blank when hints are off or it is not the player's turn
+fn hint_text(game: &BlackjackGame, you: usize, show_hints: bool) -> String {
+    if !show_hints || game.active_seat() != Some(you) {
+        return String::new();
+    }
+    // Basic strategy never takes insurance, even money included
+    if game.phase() == Phase::Insurance {
+        return "Hint: No insurance".to_string();
+    }
+    if game.phase() == Phase::Dealing {
+        return match advise(game) {
+            Some(Play::Surrender) => "Hint: Surrender".to_string(),
+            _ => "Hint: No surrender".to_string(),
+        };
+    }
+    match advise(game) {
+        Some(play) => format!("Hint: {}", play),
+        None => String::new(),
+    }
+}
+
+// Tells the player when a play went against basic strategy
+fn check_text(advice: Option<Play>, played: Play) -> String {
+    match advice {
+        Some(play) if play != played => format!("Basic strategy says {}, not {}", play, played),
+        Some(_) => "Correct play".to_string(),
+        None => String::new(),
+    }
+}
+
+// Works out the odds for the player's hand on a thread of its own, a hand like 2-3
+// against an ace takes most of a second and the table would freeze meanwhile.
+// The browser has no threads, so there the odds are worked out straight away.
+// None when odds are off or it is not the player's turn
+fn start_odds(game: &BlackjackGame, you: usize, show_odds: bool) -> Option<Receiver<Option<(DealerOdds, PlayerEv)>>> {
+    if !show_odds || game.active_seat() != Some(you) {
+        return None;
+    }
+    let (sender, receiver) = mpsc::channel();
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let game = game.clone();
+        thread::spawn(move || {
+            let _ = sender.send(odds_for(&game));
+        });
+    }
+    #[cfg(target_arch = "wasm32")]
+    let _ = sender.send(odds_for(game));
+    Some(receiver)
+}
+
+// Exact dealer chances and the value of each play from what is left in the shoe
+fn odds_text(dealer: &DealerOdds, ev: &PlayerEv) -> String {
+    let percent = |total: u8| format!("{} {:.1}%", total, dealer.total(total) * 100.0);
+    let value = |ev: Option<f64>| ev.map_or("-".to_string(), |ev| format!("{:+.3}", ev));
+    format!(
+        "Dealer busts {:.1}%\n{}  {}  {}\n{}  {}\nStand {}  Hit {}\nDouble {}  Split {}",
+        dealer.bust * 100.0,
+        percent(17),
+        percent(18),
+        percent(19),
+        percent(20),
+        percent(21),
+        value(Some(ev.stand)),
+        value(Some(ev.hit)),
+        value(ev.double),
+        value(ev.split)
+    )
+}
+
+// Session score for the count trainer, blank when it is off
+fn trainer_text(counter: &CardCounter, score: &QuizScore, on: bool) -> String {
+    if !on {
+        return String::new();
+    }
+    format!(
+        "{} trainer: {} of {} counts right ({:.0}%)",
+        counter.system().name(),
+        score.correct,
+        score.asked,
+        score.accuracy() * 100.0
+    )
+}
+
+fn toggle_text(name: &str, on: bool) -> String {
+    if on {
+        format!("{} On", name)
+    } else {
+        format!("{} Off", name)
+    }
+}
+
+// One seat for the player and up to MAX_SEATS in all
+fn valid_seats(players: &[SeatPlayer]) -> bool {
+    (1..=MAX_SEATS).contains(&players.len()) && players.iter().filter(|player| **player == SeatPlayer::Human).count() == 1
+}
+
+// Sits everybody down, the player with their own chips and the computer players with a fresh stack.
+// Returns the player's seat and a computer player for every other seat
+fn seat_table(game: &mut BlackjackGame, players: &[SeatPlayer], bankroll: u32, seed: u64) -> (usize, Vec<Option<AiPlayer>>) {
+    while game.seats().len() > 1 {
+        let _ = game.remove_seat(game.seats().len() - 1);
+    }
+    let mut you = 0;
+    let mut bots = Vec::new();
+    for (seat, player) in players.iter().enumerate() {
+        let chips = match player {
+            SeatPlayer::Human => bankroll,
+            SeatPlayer::Ai(_) => STARTING_CHIPS,
+        };
+        if seat == 0 {
+            let _ = game.set_bankroll_at(0, chips);
+        } else {
+            let _ = game.add_seat(chips);
+        }
+        match player {
+            SeatPlayer::Human => {
+                you = seat;
+                bots.push(None);
+            }
+            SeatPlayer::Ai(style) => {
+                let seed = seed.wrapping_add(seat as u64);
+                bots.push(Some(AiPlayer::new(*style, AI_NAMES[seat], AI_UNIT, game.shoe().decks(), seed)));
+            }
+        }
+    }
+    (you, bots)
+}
+
+// The computer players put their bets down, one that went broke buys back in first
+fn place_ai_bets(game: &mut BlackjackGame, bots: &[Option<AiPlayer>], events: &mut Vec<GameEvent>) {
+    for (seat, bot) in bots.iter().enumerate() {
+        let Some(bot) = bot else {
+            continue;
+        };
+        if game.seats()[seat].bankroll() < game.rules().limits.min_bet {
+            let _ = game.set_bankroll_at(seat, STARTING_CHIPS);
+        }
+        if let Ok(placed) = game.place_bet_at(seat, bot.bet(game, seat)) {
+            events.extend(placed);
+        }
+    }
+}
+
+// One decision for the computer player in the active seat
+fn ai_turn(game: &mut BlackjackGame, bot: &AiPlayer) -> Vec<GameEvent> {
+    let result = if game.phase() == Phase::Insurance {
+        let wants = bot.wants_insurance(game);
+        if wants && game.can_take_even_money() {
+            game.take_even_money()
+        } else if wants && game.max_insurance() > 0 {
+            game.take_insurance(game.max_insurance())
+        } else {
+            game.decline_insurance()
+        }
+    } else if game.phase() == Phase::Dealing {
+        match bot.play(game) {
+            Some(Play::Surrender) => game.surrender(),
+            _ => game.decline_surrender(),
         }
-    } else if show.contains("jack") || show.contains("queen") || show.contains("king") {
-        pvalue += 10;
     } else {
-        let part = &show[7..9];
-        let value = part.parse::<i32>().unwrap();
-        pvalue += value;
+        match bot.play(game) {
+            Some(Play::Hit) => game.hit(),
+            Some(Play::Double) => game.double(),
+            Some(Play::Split) => game.split(),
+            Some(Play::Surrender) => game.surrender(),
+            _ => game.stand(),
+        }
+    };
+    // A play the table does not allow becomes a stand, so the round always moves on
+    result.or_else(|_| game.stand()).unwrap_or_default()
+}
+
+// Seat screen: each of the seats can be empty, a computer player or the player.
+// Returns the seats to sit down, None when cancelled
+async fn seat_setup(current: &[SeatPlayer]) -> Option<Vec<SeatPlayer>> {
+    // What a seat button steps through
+    let choices = [
+        None,
+        Some(SeatPlayer::Ai(AiStyle::Basic)),
+        Some(SeatPlayer::Ai(AiStyle::Counter)),
+        Some(SeatPlayer::Ai(AiStyle::Hunch)),
+        Some(SeatPlayer::Human),
+    ];
+    let mut picked: Vec<Option<SeatPlayer>> = (0..MAX_SEATS).map(|seat| current.get(seat).copied()).collect();
+
+    let title = Label::new("Seats", 450.0, 80.0, 40);
+    let help = Label::new(
+        "Click a seat to change who sits there, the seats play from left to right.\nBasic plays by the book, Counter bets up when the count is good, Hunch plays on a whim.",
+        57.0,
+        160.0,
+        22,
+    );
+    let mut message = Label::new("", 57.0, 420.0, 25);
+    let mut buttons: Vec<TextButton> = (0..MAX_SEATS)
+        .map(|seat| {
+            let mut button = TextButton::new(57.0 + seat as f32 * 130.0, 250.0, 120.0, 80.0, "", BLUE, GREEN, 25);
+            button.with_round(15.0);
+            button.with_border(RED, 5.0);
+            button
+        })
+        .collect();
+    let mut apply = TextButton::new(350.0, 500.0, 150.0, 50.0, "Apply", BLUE, GREEN, 30);
+    let mut cancel = TextButton::new(520.0, 500.0, 150.0, 50.0, "Cancel", BLUE, GREEN, 30);
+    for button in [&mut apply, &mut cancel] {
+        button.with_round(15.0);
+        button.with_border(RED, 5.0);
     }
 
-    pvalue
+    loop {
+        use_virtual_resolution(1024.0, 768.0);
+        clear_background(DARKGREEN);
+
+        for (seat, button) in buttons.iter_mut().enumerate() {
+            button.set_text(picked[seat].map_or("Empty".to_string(), |player| player.to_string()));
+            if button.click() {
+                let current = choices.iter().position(|choice| *choice == picked[seat]).unwrap_or(0);
+                picked[seat] = choices[(current + 1) % choices.len()];
+                // Only one seat can be the player's
+                if picked[seat] == Some(SeatPlayer::Human) {
+                    for (other, player) in picked.iter_mut().enumerate() {
+                        if other != seat && *player == Some(SeatPlayer::Human) {
+                            *player = None;
+                        }
+                    }
+                }
+                message.set_text("");
+            }
+        }
+        if apply.click() {
+            // Empty seats are left out, the players close up to the left
+            let players: Vec<SeatPlayer> = picked.iter().flatten().copied().collect();
+            if valid_seats(&players) {
+                return Some(players);
+            }
+            message.set_text("One of the seats has to be yours");
+        }
+        if cancel.click() {
+            return None;
+        }
+
+        title.draw();
+        help.draw();
+        message.draw();
+        next_frame().await;
+    }
 }
-fn dealervalue(mut dvalue: i32, show: &str) -> i32 {
-    if show.contains("ace") {
-        dvalue += 11;
-        if dvalue > 21 {
-            dvalue -= 10;
+
+// Saves the profile, a failed save is shown instead of the stats so the player knows
+fn save_profile(store: &ProfileStore, profile: &Profile, label: &mut Label) {
+    match store.save(profile) {
+        Ok(()) => {
+            label.set_text(profile_text(profile));
         }
-    } else if show.contains("jack") || show.contains("queen") || show.contains("king") {
-        dvalue += 10;
+        // A profile that cannot be saved is kept for this game only
+        Err(ProfileError::Io(_)) => {
+            label.set_text(format!("{} (not saved)", profile_text(profile)));
+        }
+        Err(err) => {
+            label.set_text(err.to_string());
+        }
+    }
+}
+
+fn profile_text(profile: &Profile) -> String {
+    let stats = &profile.stats;
+    format!(
+        "{}: {} rounds, {} won, {} lost, net {}",
+        profile.name,
+        stats.rounds_played,
+        stats.hands_won,
+        stats.hands_lost,
+        stats.net()
+    )
+}
+
+// The amount in the bet box, 0 when it is blank or not a number
+fn bet_amount(txtbet: &TextInput) -> u32 {
+    txtbet.get_text().trim().parse().unwrap_or(0)
+}
+
+// The bet box starts with the last bet the player made
+fn bet_text(profile: &Profile) -> String {
+    if profile.preferences.last_bet > 0 {
+        profile.preferences.last_bet.to_string()
     } else {
-        let part = &show[7..9];
-        let value = part.parse::<i32>().unwrap();
-        dvalue += value;
-    }
-
-    dvalue
-}
-fn pwincheck(pvalue: i32, dvalue: i32) -> bool {
-    if pvalue > 21 {
-        return false;
-    } else if pvalue == 21 && dvalue != 21 {
-        return true;
-    } else if dvalue > 21 && pvalue < 22 {
-        return true;
-    } else if pvalue > dvalue {
-        return true;
+        String::new()
+    }
+}
+
+fn shoe_text(shoe: &Shoe) -> String {
+    format!("Shoe: {:.1} decks\n {} cards\n Seed {}", shoe.decks_remaining(), shoe.cards_remaining(), shoe.seed())
+}
+
+// The value after a command line option, given as "--name value" or "--name=value"
+fn arg_value(name: &str) -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    let prefix = format!("{}=", name);
+    args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == name {
+            Some(args.get(i + 1).cloned().unwrap_or_default())
+        } else {
+            arg.strip_prefix(&prefix).map(|value| value.to_string())
+        }
+    })
+}
+
+// The number after --seed on the command line, None to seed from the clock
+fn seed_from_args() -> Option<u64> {
+    let value = arg_value("--seed")?;
+    match value.parse() {
+        Ok(seed) => Some(seed),
+        Err(_) => {
+            eprintln!("--seed needs a whole number, not '{}'", value);
+            exit(2);
+        }
+    }
+}
+
+// Replay screen: deals the recorded rounds again from their seeds one card at a time, newest round first
+async fn replay_rounds(rounds: &[RoundRecord], tm: &TextureManager) {
+    let mut index = rounds.len() - 1;
+    let mut replay = Replay::new(rounds[index].clone());
+    let mut labels = vec![
+        Label::new("", 450.0, 100.0, 30),
+        Label::new("", 450.0, 190.0, 30),
+        Label::new("", 100.0, 600.0, 25),
+        Label::new("", 100.0, 650.0, 25),
+        Label::new("", 100.0, 690.0, 20),
+    ];
+    let mut dealer_view = HandView::new(125.0, 25.0, 650.0, 75.0, 150.0);
+    let mut seat_views = make_seat_views(replay.record().bets.len());
+
+    let mut older = TextButton::new(200.0, 400.0, 100.0, 50.0, "Older", BLUE, GREEN, 30);
+    let mut restart = TextButton::new(305.0, 400.0, 120.0, 50.0, "Restart", BLUE, GREEN, 30);
+    let mut next = TextButton::new(430.0, 400.0, 150.0, 50.0, "Next card", BLUE, GREEN, 30);
+    let mut newer = TextButton::new(585.0, 400.0, 100.0, 50.0, "Newer", BLUE, GREEN, 30);
+    let mut close = TextButton::new(690.0, 400.0, 100.0, 50.0, "Close", BLUE, GREEN, 30);
+    for button in [&mut older, &mut restart, &mut next, &mut newer, &mut close] {
+        button.with_round(15.0);
+        button.with_border(RED, 5.0);
+    }
+
+    let mut changed = true;
+    loop {
+        use_virtual_resolution(1024.0, 768.0);
+        clear_background(DARKGREEN);
+
+        older.enabled = index > 0;
+        newer.enabled = index + 1 < rounds.len();
+        next.enabled = !replay.is_finished();
+        if older.click() {
+            index -= 1;
+            replay = Replay::new(rounds[index].clone());
+            seat_views = make_seat_views(replay.record().bets.len());
+            changed = true;
+        }
+        if newer.click() {
+            index += 1;
+            replay = Replay::new(rounds[index].clone());
+            seat_views = make_seat_views(replay.record().bets.len());
+            changed = true;
+        }
+        if restart.click() {
+            replay.restart();
+            changed = true;
+        }
+        if next.click() {
+            replay.step();
+            changed = true;
+        }
+        if close.click() {
+            return;
+        }
+
+        if changed {
+            changed = false;
+            let record = replay.record();
+            let bets: Vec<String> = record.bets.iter().filter(|bet| **bet > 0).map(|bet| bet.to_string()).collect();
+            labels[2].set_text(format!("Round {} of {}   Bets {}", index + 1, rounds.len(), bets.join(", ")));
+            labels[3].set_text(format!(
+                "Seed {}   shuffle {}   from card {}   ({} of {} cards)",
+                record.seed,
+                record.shuffle + 1,
+                record.position + 1,
+                replay.cards_dealt(),
+                record.cards.len()
+            ));
+            labels[4].set_text(format!("Start the game with --seed {} to deal this shoe again", record.seed));
+            show_replay(&replay, &mut dealer_view, &mut seat_views, &mut labels, tm).await;
+        }
+
+        dealer_view.draw();
+        for view in &seat_views {
+            view.draw();
+        }
+        for label in &labels {
+            label.draw();
+        }
+
+        next_frame().await;
+    }
+}
+
+// Stats screen: this session worked out from its hand history next to the profile's lifetime totals
+async fn stats_screen(session: &[HistoryEntry], start_bankroll: u32, profile: &Profile, history: &Path) {
+    let stats = SessionStats::from_history(start_bankroll, session);
+    let lifetime = &profile.stats;
+
+    let title = Label::new("Statistics", 400.0, 60.0, 40);
+    let session_text = format!(
+        "This session\n Rounds {}   Hands {}\n Won {}   Lost {}   Pushed {}\n Blackjacks {}   Surrenders {}\n Doubles won {} of {}\n Split hands won {} of {}\n Net {:+}\n Biggest win {}   Biggest loss {}\n Longest streaks {} won, {} lost",
+        stats.rounds,
+        stats.hands,
+        stats.won,
+        stats.lost,
+        stats.pushed,
+        stats.blackjacks,
+        stats.surrenders,
+        stats.doubles_won,
+        stats.doubles,
+        stats.split_hands_won,
+        stats.split_hands,
+        stats.net,
+        stats.biggest_win,
+        stats.biggest_loss,
+        stats.longest_win_streak,
+        stats.longest_loss_streak
+    );
+    let lifetime_text = format!(
+        "Lifetime for {}\n Rounds {}   Hands {}\n Won {}   Lost {}   Pushed {}\n Blackjacks {}   Surrenders {}\n Net {:+}\n Biggest win {}\n Peak bankroll {}\n Rebuys {}",
+        profile.name,
+        lifetime.rounds_played,
+        lifetime.hands_played,
+        lifetime.hands_won,
+        lifetime.hands_lost,
+        lifetime.hands_pushed,
+        lifetime.blackjacks,
+        lifetime.surrenders,
+        lifetime.net(),
+        lifetime.biggest_win,
+        lifetime.peak_bankroll,
+        lifetime.rebuys
+    );
+    let labels = vec![
+        title,
+        Label::new(session_text, 60.0, 120.0, 22),
+        Label::new(lifetime_text, 560.0, 120.0, 22),
+        Label::new(format!("Hand history is saved to {}", history.display()), 60.0, 410.0, 18),
+        Label::new("Bankroll this session", 60.0, 445.0, 22),
+    ];
+
+    // Dashed line at the starting chips so it is easy to see if the session is up or down
+    let mut chart = LineChart::new(60.0, 460.0, 900.0, 210.0);
+    chart.set_points(stats.bankroll.iter().map(|chips| *chips as f32).collect());
+    chart.with_baseline(start_bankroll as f32);
+
+    let mut close = TextButton::new(450.0, 690.0, 120.0, 50.0, "Close", BLUE, GREEN, 30);
+    close.with_round(15.0);
+    close.with_border(RED, 5.0);
+
+    loop {
+        use_virtual_resolution(1024.0, 768.0);
+        clear_background(DARKGREEN);
+
+        if close.click() {
+            return;
+        }
+        for label in &labels {
+            label.draw();
+        }
+        chart.draw();
+
+        next_frame().await;
+    }
+}
+
+// Puts the cards of a replay on the table, with the result once the round is over
+async fn show_replay(
+    replay: &Replay,
+    dealer_view: &mut HandView,
+    seat_views: &mut [SeatView],
+    labels: &mut [Label],
+    tm: &TextureManager,
+) {
+    let back = tm.get_preload(CARD_BACK).unwrap();
+
+    let mut dealer_cards = Vec::new();
+    for (i, card) in replay.dealer().cards().iter().enumerate() {
+        if i == 1 && replay.hole_card_hidden() {
+            dealer_cards.push(back.clone());
+        } else {
+            dealer_cards.push(tm.get_preload(card.asset_path()).unwrap());
+        }
+    }
+    dealer_view.set_cards(dealer_cards).await;
+    let showing = if replay.hole_card_hidden() {
+        Hand::from_cards(&replay.dealer().cards()[..1]).best_total()
     } else {
-        return false;
-    }
-}
-fn dwincheck(pvalue: i32, dvalue: i32) -> bool {
-    if dvalue > 21 {
-        return false;
-    } else if dvalue == 21 && pvalue != 21 {
-        return true;
-    } else if pvalue > 21 && dvalue < 22 {
-        return true;
-    } else if dvalue > pvalue {
-        return true;
+        replay.dealer().best_total()
+    };
+    labels[0].set_text(format!("Dealer value:\n {}", showing));
+
+    // Seats that sat the round out are named but left empty
+    for (seat, view) in seat_views.iter_mut().enumerate() {
+        let hands = replay.seats().get(seat).map_or(Vec::new(), |hands| {
+            hands
+                .iter()
+                .map(|player| {
+                    let cards = player.hand.cards().iter().map(|card| tm.get_preload(card.asset_path()).unwrap()).collect();
+                    let result = match player.outcome {
+                        Some(outcome) => format!(" {:?}", outcome),
+                        None => String::new(),
+                    };
+                    (cards, format!("{}  Bet {}{}", player.hand.best_total(), player.bet, result))
+                })
+                .collect()
+        });
+        view.set_hands(hands).await;
+        if replay.record().bets[seat] > 0 {
+            view.set_name(format!("Seat {}", seat + 1));
+        } else {
+            view.set_name(format!("Seat {} sat out", seat + 1));
+        }
+    }
+
+    if !replay.faithful() {
+        labels[1].set_text("The seed dealt different\ncards than the record");
+    } else if replay.is_finished() {
+        labels[1].set_text("End of the round");
     } else {
-        return false;
-    }
-}
-fn deck_reset() -> Vec<&'static str> {
-    let mut deck: Vec<&'static str> = Vec::new();
-   deck = vec![
-        "assets/aceHeart.png",
-        "assets/aceDiamond.png",
-        "assets/aceClubs.png",
-        "assets/aceSpade.png",
-        "assets/jackHeart.png",
-        "assets/jackDiamond.png",
-        "assets/jackClubs.png",
-        "assets/jackSpade.png",
-        "assets/queenHeart.png",
-        "assets/queenDiamond.png",
-        "assets/queenClubs.png",
-        "assets/queenSpade.png",
-        "assets/kingHeart.png",
-        "assets/kingDiamond.png",
-        "assets/kingClubs.png",
-        "assets/kingSpade.png",
-        "assets/02clubs.png",
-        "assets/02heart.png",
-        "assets/02diamond.png",
-        "assets/02spade.png",
-        "assets/03clubs.png",
-        "assets/03heart.png",
-        "assets/03diamond.png",
-        "assets/03spade.png",
-        "assets/04clubs.png",
-        "assets/04heart.png",
-        "assets/04diamond.png",
-        "assets/04spade.png",
-        "assets/05clubs.png",
-        "assets/05heart.png",
-        "assets/05diamond.png",
-        "assets/05spade.png",
-        "assets/06clubs.png",
-        "assets/06heart.png",
-        "assets/06diamond.png",
-        "assets/06spade.png",
-        "assets/07clubs.png",
-        "assets/07heart.png",
-        "assets/07diamond.png",
-        "assets/07spade.png",
-        "assets/08clubs.png",
-        "assets/08heart.png",
-        "assets/08diamond.png",
-        "assets/08spade.png",
-        "assets/09clubs.png",
-        "assets/09heart.png",
-        "assets/09diamond.png",
-        "assets/09spade.png",
-        "assets/10clubs.png",
-        "assets/10heart.png",
-        "assets/10diamond.png",
-        "assets/10spade.png",
+        labels[1].set_text("");
+    }
+}
+
+// Network table: the server deals and keeps everybody's chips, this screen shows its table and sends what the buttons do
+async fn network_table(addr: &str, name: &str, keys: &KeyBindings, tm: &TextureManager) {
+    let mut labels = vec![
+        Label::new("", 425.0, 525.0, 30),
+        Label::new("", 20.0, 195.0, 22),
+        Label::new("", 450.0, 100.0, 30),
+        Label::new(format!("Joining the table at {}", addr), 450.0, 190.0, 30),
+        Label::new("", 800.0, 50.0, 25),
+        Label::new("", 100.0, 600.0, 25),
     ];
-    deck
-}
\ No newline at end of file
+    let mut dealer_view = HandView::new(125.0, 25.0, 650.0, 75.0, 150.0);
+    let mut seat_views: Vec<SeatView> = Vec::new();
+    let mut insurance_box = MessageBox::confirm("Insurance", "");
+    let mut surrender_box = MessageBox::confirm("Early Surrender", "The dealer shows a ten.\nSurrender half your bet before the peek?");
+
+    let mut next = TextButton::new(200.0, 400.0, 100.0, 50.0, "Next", BLUE, GREEN, 30);
+    let mut stand = TextButton::new(300.0, 400.0, 100.0, 50.0, "Stand", BLUE, GREEN, 30);
+    let mut hit = TextButton::new(400.0, 400.0, 100.0, 50.0, "Hit", BLUE, GREEN, 30);
+    let mut deal = TextButton::new(500.0, 400.0, 100.0, 50.0, "Deal", BLUE, GREEN, 30);
+    let mut double = TextButton::new(600.0, 400.0, 100.0, 50.0, "Double", BLUE, GREEN, 30);
+    let mut split = TextButton::new(700.0, 400.0, 100.0, 50.0, "Split", BLUE, GREEN, 30);
+    let mut surrender = TextButton::new(800.0, 400.0, 140.0, 50.0, "Surrender", BLUE, GREEN, 30);
+    let mut bet = TextButton::new(410.0, 500.0, 100.0, 40.0, "Bet", BLUE, GREEN, 25);
+    let mut rebuy = TextButton::new(810.0, 140.0, 130.0, 40.0, "Rebuy", BLUE, GREEN, 25);
+    let mut leave = TextButton::new(810.0, 190.0, 130.0, 40.0, "Leave", BLUE, GREEN, 25);
+    for button in [&mut next, &mut stand, &mut hit, &mut deal, &mut double, &mut split, &mut surrender, &mut bet, &mut rebuy, &mut leave] {
+        button.with_round(15.0);
+        button.with_border(RED, 5.0);
+    }
+    bind_key(&mut hit, &keys.hit);
+    bind_key(&mut stand, &keys.stand);
+    bind_key(&mut double, &keys.double);
+    bind_key(&mut split, &keys.split);
+    bind_key(&mut surrender, &keys.surrender);
+    bind_key(&mut deal, &keys.deal);
+    bind_key(&mut next, &keys.deal);
+    bind_key(&mut bet, &keys.confirm);
+    let mut txtbet = TextInput::new(100.0, 500.0, 300.0, 40.0, 25.0);
+    txtbet.with_colors(WHITE, RED, BLACK, WHITE);
+    txtbet.set_prompt("Enter Bet Here");
+
+    // Messages from the server come in on a thread of their own so the screen never waits for the network
+    let (mut stream, receiver) = match connect_table(addr) {
+        Ok(connected) => connected,
+        Err(err) => {
+            labels[3].set_text(format!("Could not reach {}\n{}", addr, err));
+            let (_, receiver) = mpsc::channel();
+            (None, receiver)
+        }
+    };
+    send_table(&mut stream, ClientMessage::Join { name: name.to_string() });
+    let mut table: Option<TableState> = None;
+
+    loop {
+        use_virtual_resolution(1024.0, 768.0);
+        clear_background(DARKGREEN);
+
+        // Only the newest table matters, errors are shown as they come
+        let mut changed = false;
+        loop {
+            match receiver.try_recv() {
+                Ok(ServerMessage::Table(state)) => {
+                    table = Some(state);
+                    changed = true;
+                }
+                Ok(ServerMessage::Error(text)) => {
+                    labels[3].set_text(text);
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    if stream.take().is_some() {
+                        labels[3].set_text("Lost the connection to the table");
+                    }
+                    break;
+                }
+            }
+        }
+
+        let seated = stream.is_some() && table.as_ref().is_some_and(|state| state.you.is_some());
+        let phase = table.as_ref().map(|state| state.phase);
+        let your_turn = seated && phase == Some(Phase::PlayerTurn) && table.as_ref().is_some_and(|state| state.your_turn());
+        let can = |allowed: fn(&TableState) -> bool| your_turn && table.as_ref().is_some_and(allowed);
+        bet.enabled = seated && phase == Some(Phase::Betting);
+        deal.enabled = seated && phase == Some(Phase::Betting);
+        next.enabled = seated && phase == Some(Phase::Settlement);
+        rebuy.enabled = bet.enabled && table.as_ref().is_some_and(TableState::can_rebuy);
+        stand.enabled = your_turn;
+        hit.enabled = can(|state| state.can_hit);
+        double.enabled = can(|state| state.can_double);
+        split.enabled = can(|state| state.can_split);
+        surrender.enabled = can(|state| state.can_surrender);
+        // Only Bet, on the confirm key, takes a key press while typing in the bet box
+        for button in [&mut hit, &mut stand, &mut double, &mut split, &mut surrender, &mut deal, &mut next] {
+            button.keys_enabled = !txtbet.is_active();
+        }
+
+        if bet.click() {
+            match txtbet.get_text().trim().parse::<u32>() {
+                Ok(amount) => {
+                    send_table(&mut stream, ClientMessage::Bet { amount });
+                    // The deal and play keys would be typed into the box otherwise
+                    txtbet.set_active(false);
+                }
+                Err(_) => {
+                    labels[3].set_text("Invalid bet");
+                }
+            }
+        }
+        for (button, message) in [
+            (&deal, ClientMessage::Deal),
+            (&next, ClientMessage::NextRound),
+            (&hit, ClientMessage::Hit),
+            (&stand, ClientMessage::Stand),
+            (&double, ClientMessage::Double),
+            (&split, ClientMessage::Split),
+            (&surrender, ClientMessage::Surrender),
+            (&rebuy, ClientMessage::Rebuy),
+        ] {
+            if button.click() {
+                labels[3].set_text("");
+                send_table(&mut stream, message);
+            }
+        }
+        if leave.click() {
+            send_table(&mut stream, ClientMessage::Leave);
+            return;
+        }
+
+        if changed {
+            if let Some(state) = &table {
+                if seat_views.len() != state.seats.len() {
+                    seat_views = make_seat_views(state.seats.len());
+                }
+                show_network_table(state, &mut seat_views, &mut dealer_view, &mut labels, tm).await;
+                labels[4].set_text(format!("Table {}\n {} cards", addr, state.cards_remaining));
+                if state.phase == Phase::Settlement {
+                    if let Some(seat) = state.you.filter(|seat| !state.seats[*seat].hands.is_empty()) {
+                        labels[3].set_text(network_outcome_text(&state.seats[seat]));
+                    }
+                }
+                // Insurance is asked here, once for every table the server sends
+                if state.phase == Phase::Insurance && state.your_turn() && !insurance_box.is_visible() {
+                    if state.can_take_even_money {
+                        insurance_box = MessageBox::confirm("Even Money", "You have blackjack and the dealer shows an ace.\nTake even money?");
+                        insurance_box.show();
+                    } else if state.max_insurance > 0 {
+                        let message = format!("The dealer shows an ace.\nTake insurance for {}?", state.max_insurance);
+                        insurance_box = MessageBox::confirm("Insurance", message);
+                        insurance_box.show();
+                    } else {
+                        send_table(&mut stream, ClientMessage::DeclineInsurance);
+                    }
+                }
+                if state.phase == Phase::Dealing && state.your_turn() && !surrender_box.is_visible() {
+                    surrender_box.show();
+                }
+            }
+        }
+
+        if let Some(result) = insurance_box.draw() {
+            let message = match (result, &table) {
+                (MessageBoxResult::ButtonPressed(0), Some(state)) if state.can_take_even_money => ClientMessage::EvenMoney,
+                (MessageBoxResult::ButtonPressed(0), Some(state)) => ClientMessage::Insurance { amount: state.max_insurance },
+                _ => ClientMessage::DeclineInsurance,
+            };
+            send_table(&mut stream, message);
+        }
+        if let Some(result) = surrender_box.draw() {
+            let message = match result {
+                MessageBoxResult::ButtonPressed(0) => ClientMessage::Surrender,
+                _ => ClientMessage::DeclineSurrender,
+            };
+            send_table(&mut stream, message);
+        }
+
+        dealer_view.draw();
+        for view in &seat_views {
+            view.draw();
+        }
+        if let Some(state) = &table {
+            if let Some(seat) = state.active_seat {
+                if let Some(area) = seat_views.get(seat).and_then(|view| view.hand_bounds(state.active_hand.unwrap_or(0))) {
+                    draw_rectangle_lines(area.x - 5.0, area.y - 5.0, area.w + 10.0, area.h + 10.0, 4.0, YELLOW);
+                }
+            }
+        }
+        for label in &labels {
+            label.draw();
+        }
+        txtbet.draw();
+        insurance_box.draw();
+        surrender_box.draw();
+
+        next_frame().await;
+    }
+}
+
+// Connects to a table server, the receiver gets every message it sends until the connection closes
+fn connect_table(addr: &str) -> std::io::Result<(Option<TcpStream>, Receiver<ServerMessage>)> {
+    let stream = TcpStream::connect(addr)?;
+    let mut reader = stream.try_clone()?;
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        while let Ok(message) = read_message(&mut reader) {
+            if sender.send(message).is_err() {
+                return;
+            }
+        }
+    });
+    Ok((Some(stream), receiver))
+}
+
+// Sends a message to the table, a connection that fails is dropped
+fn send_table(stream: &mut Option<TcpStream>, message: ClientMessage) {
+    if let Some(connection) = stream {
+        if write_message(connection, &message).is_err() {
+            *stream = None;
+        }
+    }
+}
+
+// Puts the server's table on the screen
+async fn show_network_table(state: &TableState, seat_views: &mut [SeatView], dealer_view: &mut HandView, labels: &mut [Label], tm: &TextureManager) {
+    let back = tm.get_preload(CARD_BACK).unwrap();
+
+    for (seat, view) in seat_views.iter_mut().enumerate() {
+        let player = &state.seats[seat];
+        let hands = if !player.hands.is_empty() {
+            player
+                .hands
+                .iter()
+                .map(|hand| {
+                    let cards = hand.cards.iter().map(|card| tm.get_preload(card.asset_path()).unwrap()).collect();
+                    let text = format!("{}  Bet {}{}", Hand::from_cards(&hand.cards).best_total(), hand.bet, result_text(hand.outcome));
+                    (cards, text)
+                })
+                .collect()
+        } else if state.phase == Phase::Betting && !player.name.is_empty() {
+            let text = if player.bet > 0 { format!("Bet {}", player.bet) } else { String::new() };
+            vec![(vec![back.clone(), back.clone()], text)]
+        } else {
+            Vec::new()
+        };
+        view.set_hands(hands).await;
+        let name = match (player.name.is_empty(), state.you == Some(seat)) {
+            (true, _) => "Empty seat".to_string(),
+            (false, true) => format!("{} (you)  {}", player.name, player.bankroll),
+            (false, false) => format!("{}  {}", player.name, player.bankroll),
+        };
+        view.set_name(name);
+    }
+
+    // The server leaves the hole card out while it is face down
+    let mut dealer_cards: Vec<_> = state.dealer.iter().map(|card| tm.get_preload(card.asset_path()).unwrap()).collect();
+    if state.hole_card_hidden {
+        dealer_cards.push(back.clone());
+    }
+    if dealer_cards.is_empty() {
+        dealer_cards = vec![back.clone(), back.clone()];
+    }
+    dealer_view.set_cards(dealer_cards).await;
+
+    let chips = state.you.map_or(0, |seat| state.seats[seat].bankroll);
+    labels[0].set_text(if state.you.is_some() { format!("Chips:\n {}", chips) } else { "Waiting for a seat".to_string() });
+    labels[1].set_text(match (state.phase, state.active_seat) {
+        (_, Some(seat)) if state.you == Some(seat) => "Your turn".to_string(),
+        (_, Some(seat)) => format!("{} is playing", state.seats[seat].name),
+        (Phase::DealerTurn, None) => "Dealer's turn".to_string(),
+        _ => String::new(),
+    });
+    labels[2].set_text(format!("Dealer value:\n {}", Hand::from_cards(&state.dealer).best_total()));
+    labels[5].set_text(&state.rules);
+}
+
+// How the round went for a seat at a network table
+fn network_outcome_text(seat: &SeatState) -> String {
+    let staked = seat.hands.iter().map(|hand| hand.bet as i64).sum::<i64>() + seat.insurance as i64;
+    match seat.payout as i64 - staked {
+        n if n > 0 => format!("You Win {}", n),
+        n if n < 0 => format!("You lose {}", -n),
+        _ => "You Draw".to_string(),
+    }
+}
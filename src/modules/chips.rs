@@ -0,0 +1,161 @@
+/*
+Program Details: Casino chips, a rack to bet with and a stack to show a bet
+
+To import you need:
+
+In your mod.rs file located in the modules folder add the following to the end of the file
+    pub mod chips;
+
+Then add the following with the use commands:
+use crate::modules::chips::{ChipClick, ChipRack, ChipStack};
+
+Usage examples:
+1. Create a rack of chips (1, 5, 25, 100 and 500) in a row:
+    let mut rack = ChipRack::new(
+        565.0,  // x position of the centre of the first chip
+        520.0,  // y position of the centres
+        20.0,   // radius of a chip
+    );
+
+2. In your game loop, click() draws the rack and tells you which chip was clicked:
+    rack.enabled = game.phase() == Phase::Betting;
+    match rack.click() {
+        Some(ChipClick::Add(value)) => bet += value,       // left click
+        Some(ChipClick::Remove(value)) => bet -= value,    // right click
+        None => {}
+    }
+
+3. Show a bet as a stack of chips, biggest chips at the bottom:
+    let mut stack = ChipStack::new(55.0, 520.0, 22.0);   // centre of the bottom chip and radius
+    stack.set_amount(125);
+    stack.draw();
+
+A stack shows at most MAX_STACK_CHIPS chips, the amount is written under it.
+*/
+#[cfg(feature = "scale")]
+use crate::modules::scale::mouse_position_world as mouse_position;
+use macroquad::prelude::*;
+
+/// Chip values in the rack, smallest first
+pub const CHIP_VALUES: [u32; 5] = [1, 5, 25, 100, 500];
+
+/// Most chips drawn in one stack
+pub const MAX_STACK_CHIPS: usize = 10;
+
+/// What happened to a chip in the rack
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChipClick {
+    Add(u32),
+    Remove(u32),
+}
+
+// Face and text colours of a chip
+fn chip_colors(value: u32) -> (Color, Color) {
+    match value {
+        1 => (WHITE, BLACK),
+        5 => (RED, WHITE),
+        25 => (DARKGREEN, WHITE),
+        100 => (BLACK, WHITE),
+        _ => (PURPLE, WHITE),
+    }
+}
+
+/// Draws one chip with its value in the middle, `alpha` below 1.0 fades it
+pub fn draw_chip(x: f32, y: f32, radius: f32, value: u32, alpha: f32) {
+    let (face, text) = chip_colors(value);
+    let fade = |color: Color| Color::new(color.r, color.g, color.b, color.a * alpha);
+    draw_circle(x, y, radius, fade(face));
+    draw_circle_lines(x, y, radius, 2.0, fade(GRAY));
+    // The ring of a real chip, in white so it shows on every colour
+    draw_circle_lines(x, y, radius * 0.72, 2.0, fade(Color::new(1.0, 1.0, 1.0, 0.8)));
+
+    let label = value.to_string();
+    let font_size = (radius * 0.9) as u16;
+    let size = measure_text(&label, None, font_size, 1.0);
+    draw_text(&label, x - size.width / 2.0, y + size.offset_y / 2.0, font_size as f32, fade(text));
+}
+
+pub struct ChipRack {
+    x: f32,
+    y: f32,
+    radius: f32,
+    pub enabled: bool,
+}
+
+impl ChipRack {
+    pub fn new(x: f32, y: f32, radius: f32) -> Self {
+        Self { x, y, radius, enabled: true }
+    }
+
+    // Centre of the chip for CHIP_VALUES[index]
+    fn centre(&self, index: usize) -> Vec2 {
+        vec2(self.x + index as f32 * self.radius * 2.25, self.y)
+    }
+
+    /// Draws the chips and returns the one clicked this frame, left click adds and right click removes
+    pub fn click(&self) -> Option<ChipClick> {
+        let (mouse_x, mouse_y) = mouse_position();
+        let mouse = vec2(mouse_x, mouse_y);
+        let mut clicked = None;
+        for (index, value) in CHIP_VALUES.iter().enumerate() {
+            let centre = self.centre(index);
+            let hovered = self.enabled && centre.distance(mouse) <= self.radius;
+            // A hovered chip lifts a little
+            let lift = if hovered { 3.0 } else { 0.0 };
+            draw_chip(centre.x, centre.y - lift, self.radius, *value, if self.enabled { 1.0 } else { 0.5 });
+            if hovered && is_mouse_button_pressed(MouseButton::Left) {
+                clicked = Some(ChipClick::Add(*value));
+            } else if hovered && is_mouse_button_pressed(MouseButton::Right) {
+                clicked = Some(ChipClick::Remove(*value));
+            }
+        }
+        clicked
+    }
+}
+
+pub struct ChipStack {
+    x: f32,
+    y: f32,
+    radius: f32,
+    amount: u32,
+    chips: Vec<u32>, // Bottom chip first
+}
+
+impl ChipStack {
+    pub fn new(x: f32, y: f32, radius: f32) -> Self {
+        Self {
+            x,
+            y,
+            radius,
+            amount: 0,
+            chips: Vec::new(),
+        }
+    }
+
+    /// Makes the stack up from the biggest chips that fit
+    pub fn set_amount(&mut self, amount: u32) {
+        if amount == self.amount {
+            return;
+        }
+        self.amount = amount;
+        self.chips.clear();
+        let mut left = amount;
+        for value in CHIP_VALUES.iter().rev() {
+            while left >= *value {
+                self.chips.push(*value);
+                left -= value;
+            }
+        }
+    }
+
+    pub fn draw(&self) {
+        for (i, value) in self.chips.iter().take(MAX_STACK_CHIPS).enumerate() {
+            draw_chip(self.x, self.y - i as f32 * 5.0, self.radius, *value, 1.0);
+        }
+        if self.amount > 0 {
+            let label = self.amount.to_string();
+            let size = measure_text(&label, None, 22, 1.0);
+            draw_text(&label, self.x - size.width / 2.0, self.y + self.radius + 20.0, 22.0, WHITE);
+        }
+    }
+}
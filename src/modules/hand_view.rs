@@ -0,0 +1,201 @@
+/*
+Program Details: A row of overlapping card images that squeezes together to fit its width
+
+To import you need:
+
+In your mod.rs file located in the modules folder add the following to the end of the file
+    pub mod hand_view;
+
+Then add the following with the use commands:
+use crate::modules::hand_view::HandView;
+
+Usage examples:
+1. Create a hand view:
+    let mut view = HandView::new(
+        125.0,  // x position of the first card
+        200.0,  // y position
+        800.0,  // widest the row of cards can get
+        75.0,   // card width
+        150.0,  // card height
+    );
+
+2. Put cards in it (any number, it makes more images when it needs them):
+    let cards = vec![
+        texture_manager.get_preload("assets/ah.png").unwrap(),
+        texture_manager.get_preload("assets/backcard.png").unwrap(),
+    ];
+    view.set_cards(cards).await;
+
+3. Change the layout:
+    view.with_spacing(40.0);            // widest gap between cards (default: 55)
+    view.set_position(50.0, 200.0);
+    view.set_max_width(400.0);          // cards overlap more to stay inside this width
+
+4. Deal the cards out of a shoe instead of having them appear:
+    view.with_deal_from(900.0, 20.0);   // where new cards slide in from
+    view.set_deal_speed(0.3);           // seconds each card takes, 0 turns the animation off
+    if view.is_animating() { ... }      // wait for the cards before taking input
+
+5. Draw it in your game loop:
+    view.draw();
+
+    // The space the cards take up, handy for highlighting a hand
+    let area = view.bounds();
+
+Cards are spaced out by the spacing until the row would get wider than max_width,
+then the gap shrinks so the last card still ends at x + max_width.
+
+With a deal position set, set_cards() slides new cards in one after another and
+turns over a card that was face down (CARD_BACK_FILE) before. A row of nothing
+but face down cards is a placeholder, it shows up straight away and the cards
+after it are dealt in as new cards.
+*/
+use macroquad::prelude::*;
+use macroquad::texture::Texture2D;
+
+use crate::modules::still_image::StillImage;
+use crate::modules::tween::{Easing, Tween};
+
+/// The image of a face down card, the cards that get turned over
+pub const CARD_BACK_FILE: &str = "assets/backcard.png";
+
+pub struct HandView {
+    x: f32,
+    y: f32,
+    max_width: f32,
+    card_width: f32,
+    card_height: f32,
+    spacing: f32,            // Gap between the left edges of two cards when there is room
+    images: Vec<StillImage>, // One image per card, first card on the left
+    deal_from: Option<Vec2>, // Where new cards slide in from, None for no animation
+    deal_speed: f32,         // Seconds a card takes to slide in or turn over
+}
+
+impl HandView {
+    pub fn new(x: f32, y: f32, max_width: f32, card_width: f32, card_height: f32) -> Self {
+        Self {
+            x,
+            y,
+            max_width,
+            card_width,
+            card_height,
+            spacing: 55.0,
+            images: Vec::new(),
+            deal_from: None,
+            deal_speed: 0.3,
+        }
+    }
+
+    // Sets the widest gap between cards
+    pub fn with_spacing(&mut self, spacing: f32) -> &mut Self {
+        self.spacing = spacing;
+        self.layout(false);
+        self
+    }
+
+    // Sets where new cards slide in from
+    pub fn with_deal_from(&mut self, x: f32, y: f32) -> &mut Self {
+        self.deal_from = Some(vec2(x, y));
+        self
+    }
+
+    pub fn set_deal_speed(&mut self, seconds: f32) {
+        self.deal_speed = seconds.max(0.0);
+    }
+
+    // True while cards are still sliding or turning over
+    pub fn is_animating(&self) -> bool {
+        self.images.iter().any(|image| image.is_animating())
+    }
+
+    pub fn set_position(&mut self, x: f32, y: f32) {
+        self.x = x;
+        self.y = y;
+        self.layout(false);
+    }
+
+    pub fn set_max_width(&mut self, max_width: f32) {
+        self.max_width = max_width;
+        self.layout(false);
+    }
+
+    // Replaces the cards shown, reusing the images already made.
+    // With a deal position new cards slide in and face down cards that come up are turned over
+    pub async fn set_cards(&mut self, cards: Vec<(Texture2D, Option<Vec<u8>>, String)>) {
+        let animate = self.deal_from.is_some() && self.deal_speed > 0.0;
+        // Cards already on the table, nothing when they were only placeholders
+        let mut before: Vec<String> = self.images.iter().map(|image| image.get_filename().to_string()).collect();
+        if all_face_down(before.iter().map(|file| file.as_str())) {
+            before.clear();
+        }
+        let animate = animate && !all_face_down(cards.iter().map(|card| card.2.as_str()));
+
+        self.images.truncate(cards.len());
+        while self.images.len() < cards.len() {
+            let image = StillImage::new("", self.card_width, self.card_height, self.x, self.y, true, 1.0).await;
+            self.images.push(image);
+        }
+        self.layout(animate);
+
+        let mut dealt = 0;
+        for (i, (image, card)) in self.images.iter_mut().zip(cards).enumerate() {
+            match before.get(i) {
+                Some(file) if *file == card.2 => {}
+                Some(file) if animate && file == CARD_BACK_FILE => image.flip_to(card, self.deal_speed),
+                _ => {
+                    image.set_preload(card);
+                    if let (true, Some(from)) = (animate, self.deal_from) {
+                        // Each new card leaves the shoe a little after the one before it
+                        let slide = Tween::new(from, image.pos(), self.deal_speed, Easing::EaseOut);
+                        image.animate_position(slide.with_delay(dealt as f32 * self.deal_speed * 0.6));
+                        dealt += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    // Gap between cards right now, smaller than the spacing once the cards have to squeeze in
+    pub fn step(&self) -> f32 {
+        if self.images.len() < 2 {
+            return self.spacing;
+        }
+        let room = (self.max_width - self.card_width).max(0.0);
+        (room / (self.images.len() - 1) as f32).min(self.spacing)
+    }
+
+    // The area the cards cover
+    pub fn bounds(&self) -> Rect {
+        let cards = self.images.len().max(1);
+        let width = self.card_width + self.step() * (cards - 1) as f32;
+        Rect::new(self.x, self.y, width, self.card_height)
+    }
+
+    // Moves every card into place after the cards or the layout change, sliding them there when animated
+    fn layout(&mut self, animate: bool) {
+        let step = self.step();
+        for (i, image) in self.images.iter_mut().enumerate() {
+            let spot = vec2(self.x + step * i as f32, self.y);
+            if image.pos() == spot {
+                continue;
+            }
+            if animate {
+                image.animate_position(Tween::new(image.drawn_pos(), spot, self.deal_speed, Easing::EaseOut));
+            } else {
+                image.set_position(spot);
+            }
+        }
+    }
+
+    // Draws the cards left to right so each one sits on top of the one before
+    pub fn draw(&self) {
+        for image in &self.images {
+            image.draw();
+        }
+    }
+}
+
+// True for a row of face down cards, the placeholder shown between rounds
+fn all_face_down<'a>(mut files: impl Iterator<Item = &'a str>) -> bool {
+    files.all(|file| file == CARD_BACK_FILE)
+}
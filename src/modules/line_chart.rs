@@ -0,0 +1,146 @@
+/*
+Program Details: A simple line chart drawn with lines and rectangles
+
+To import you need:
+
+In your mod.rs file located in the modules folder add the following to the end of the file
+    pub mod line_chart;
+
+Then add the following with the use commands:
+use crate::modules::line_chart::LineChart;
+
+Usage examples:
+1. Create a chart:
+    let mut chart = LineChart::new(
+        100.0,  // x position of the left edge
+        300.0,  // y position of the top edge
+        800.0,  // width
+        250.0,  // height
+    );
+
+2. Give it the values, they are spread evenly from left to right:
+    chart.set_points(vec![500.0, 525.0, 475.0, 550.0]);
+
+3. Draw a dashed line across the chart at a value, like the starting bankroll:
+    chart.with_baseline(500.0);
+
+4. Draw it in your game loop:
+    chart.draw();
+
+The chart scales itself to fit the lowest and highest value (and the baseline),
+with the two shown at the left of the chart.
+*/
+use macroquad::prelude::*;
+
+pub struct LineChart {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    points: Vec<f32>,
+    baseline: Option<f32>,
+    background: Color,
+    line_color: Color,
+    text_color: Color,
+}
+
+impl LineChart {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+            points: Vec::new(),
+            baseline: None,
+            background: Color::new(0.0, 0.0, 0.0, 0.3),
+            line_color: YELLOW,
+            text_color: WHITE,
+        }
+    }
+
+    pub fn with_baseline(&mut self, value: f32) -> &mut Self {
+        self.baseline = Some(value);
+        self
+    }
+
+    pub fn set_points(&mut self, points: Vec<f32>) {
+        self.points = points;
+    }
+
+    // Lowest and highest value on the chart, kept apart so a flat line still has room
+    fn range(&self) -> (f32, f32) {
+        let mut low = f32::MAX;
+        let mut high = f32::MIN;
+        for value in self.points.iter().chain(self.baseline.iter()) {
+            low = low.min(*value);
+            high = high.max(*value);
+        }
+        if low > high {
+            return (0.0, 1.0);
+        }
+        if high - low < 1.0 {
+            return (low - 1.0, high + 1.0);
+        }
+        (low, high)
+    }
+
+    // Screen y for a value, the highest value at the top
+    fn y_for(&self, value: f32, low: f32, high: f32) -> f32 {
+        self.y + self.height - (value - low) / (high - low) * self.height
+    }
+
+    pub fn draw(&self) {
+        draw_rectangle(self.x, self.y, self.width, self.height, self.background);
+        draw_line(self.x, self.y, self.x, self.y + self.height, 2.0, self.text_color);
+        draw_line(
+            self.x,
+            self.y + self.height,
+            self.x + self.width,
+            self.y + self.height,
+            2.0,
+            self.text_color,
+        );
+
+        let (low, high) = self.range();
+        draw_text(&format!("{}", high.round()), self.x + 5.0, self.y + 18.0, 20.0, self.text_color);
+        draw_text(
+            &format!("{}", low.round()),
+            self.x + 5.0,
+            self.y + self.height - 5.0,
+            20.0,
+            self.text_color,
+        );
+
+        if let Some(baseline) = self.baseline {
+            // Dashes 10 wide with a gap of 10
+            let y = self.y_for(baseline, low, high);
+            let mut dash = self.x;
+            while dash < self.x + self.width {
+                let end = (dash + 10.0).min(self.x + self.width);
+                draw_line(dash, y, end, y, 1.0, self.text_color);
+                dash += 20.0;
+            }
+        }
+
+        if self.points.len() < 2 {
+            if let Some(value) = self.points.first() {
+                draw_circle(self.x, self.y_for(*value, low, high), 3.0, self.line_color);
+            }
+            return;
+        }
+        let step = self.width / (self.points.len() - 1) as f32;
+        for (i, pair) in self.points.windows(2).enumerate() {
+            let x1 = self.x + i as f32 * step;
+            let x2 = x1 + step;
+            draw_line(
+                x1,
+                self.y_for(pair[0], low, high),
+                x2,
+                self.y_for(pair[1], low, high),
+                3.0,
+                self.line_color,
+            );
+        }
+    }
+}
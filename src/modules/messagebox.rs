@@ -210,11 +210,7 @@ impl MessageBox {
         let title = title.into();
         let message = message.into();
         let buttons: Vec<String> = buttons.into_iter().map(|b| b.into()).collect();
-        let default_button = if let Some(idx) = default_button {
-            if idx < buttons.len() { Some(idx) } else { None }
-        } else {
-            None
-        };
+        let default_button = default_button.filter(|&idx| idx < buttons.len());
         
         Self {
             visible: false,
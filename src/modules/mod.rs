@@ -19,4 +19,9 @@ use crate::modules::grid::draw_grid;
  pub mod text_button;
  pub mod scale;
  pub mod text_input;
- pub mod messagebox;
\ No newline at end of file
+ pub mod messagebox;
+ pub mod hand_view;
+ pub mod line_chart;
+ pub mod seat_view;
+ pub mod chips;
+ pub mod tween;
\ No newline at end of file
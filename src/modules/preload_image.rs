@@ -149,6 +149,7 @@ impl Default for LoadingScreenOptions {
 /// This reduces memory usage and prevents flickering when switching images
 #[derive(Clone)]
 pub struct TextureManager {
+    #[allow(clippy::type_complexity)]
     textures: Arc<Mutex<HashMap<String, (Texture2D, Option<Vec<u8>>)>>>,
     load_order: Arc<Mutex<Vec<String>>>, // Store just the order textures were loaded in
 }
@@ -316,7 +317,7 @@ impl TextureManager {
             
             // Display current file if available
             if loaded_assets > 0 && loaded_assets < total_assets {
-                let file_name = assets[loaded_assets].split('/').last().unwrap_or("");
+                let file_name = assets[loaded_assets].split('/').next_back().unwrap_or("");
                 let file_text = format!("Loading: {}", file_name);
                 draw_text(
                     &file_text,
@@ -47,7 +47,7 @@ thread_local! {
     });
     
     // We'll store the current virtual resolution here
-    static VIRTUAL_RESOLUTION: RefCell<(f32, f32)> = RefCell::new((1024.0, 768.0));
+    static VIRTUAL_RESOLUTION: RefCell<(f32, f32)> = const { RefCell::new((1024.0, 768.0)) };
 }
 
 /// Sets the camera to the virtual resolution and adjusts the scale
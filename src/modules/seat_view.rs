@@ -0,0 +1,144 @@
+/*
+Program Details: One seat at the table, its hands side by side with a label under each
+
+To import you need:
+
+In your mod.rs file located in the modules folder add the following to the end of the file
+    pub mod seat_view;
+
+Then add the following with the use commands:
+use crate::modules::seat_view::SeatView;
+
+Usage examples:
+1. Create a seat:
+    let mut seat = SeatView::new(
+        125.0,  // x position of the left edge
+        200.0,  // y position of the top of the cards
+        800.0,  // width of the seat, its hands share it
+        75.0,   // card width
+        150.0,  // card height
+    );
+
+2. Name it and give it hands, each hand is its cards and the text shown under it:
+    seat.set_name("You  500");
+    let hands = vec![
+        (vec![texture_manager.get_preload("assets/ah.png").unwrap()], "11  Bet 10".to_string()),
+    ];
+    seat.set_hands(hands).await;
+
+3. Deal the cards in from the shoe (see HandView), and hold off input while they move:
+    seat.set_deal(870.0, 70.0, 0.3);
+    if seat.is_animating() { ... }
+
+4. Draw it in your game loop:
+    seat.draw();
+
+    // The space a hand's cards take up, handy for highlighting the hand being played
+    let area = seat.hand_bounds(0);
+
+The name goes under the cards and the hand labels under the name.
+*/
+use macroquad::prelude::*;
+use macroquad::texture::Texture2D;
+
+use crate::modules::hand_view::HandView;
+use crate::modules::label::Label;
+
+/// A card image the way the texture manager hands it out
+pub type CardImage = (Texture2D, Option<Vec<u8>>, String);
+
+pub struct SeatView {
+    x: f32,
+    y: f32,
+    width: f32,
+    card_width: f32,
+    card_height: f32,
+    name: Label,
+    views: Vec<HandView>, // One row of cards per hand, left to right
+    labels: Vec<Label>,
+    deal_from: Option<Vec2>, // Passed on to the rows of cards, see HandView
+    deal_speed: f32,
+}
+
+impl SeatView {
+    pub fn new(x: f32, y: f32, width: f32, card_width: f32, card_height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            card_width,
+            card_height,
+            name: Label::new("", x, y + card_height + 20.0, 20),
+            views: Vec::new(),
+            labels: Vec::new(),
+            deal_from: None,
+            deal_speed: 0.3,
+        }
+    }
+
+    /// Deals new cards in from (x, y), `seconds` for each card, see HandView
+    pub fn set_deal(&mut self, x: f32, y: f32, seconds: f32) {
+        self.deal_from = Some(vec2(x, y));
+        self.deal_speed = seconds;
+        for view in &mut self.views {
+            view.with_deal_from(x, y).set_deal_speed(seconds);
+        }
+    }
+
+    /// True while any of the seat's cards are still moving
+    pub fn is_animating(&self) -> bool {
+        self.views.iter().any(|view| view.is_animating())
+    }
+
+    pub fn set_name<T: Into<String>>(&mut self, text: T) {
+        self.name.set_text(text);
+    }
+
+    /// Replaces the hands, making or dropping rows of cards to match
+    pub async fn set_hands(&mut self, hands: Vec<(Vec<CardImage>, String)>) {
+        self.views.truncate(hands.len());
+        self.labels.truncate(hands.len());
+        while self.views.len() < hands.len() {
+            let mut view = HandView::new(self.x, self.y, self.width, self.card_width, self.card_height);
+            // Keep the overlap the same as a full size card
+            view.with_spacing(self.card_width * 0.73);
+            if let Some(from) = self.deal_from {
+                view.with_deal_from(from.x, from.y).set_deal_speed(self.deal_speed);
+            }
+            self.views.push(view);
+            self.labels.push(Label::new("", self.x, self.y + self.card_height + 40.0, 18));
+        }
+        self.layout();
+        for (i, (cards, text)) in hands.into_iter().enumerate() {
+            self.views[i].set_cards(cards).await;
+            self.labels[i].set_text(text);
+        }
+    }
+
+    // Splits the seat's width between its hands
+    fn layout(&mut self) {
+        let count = self.views.len().max(1);
+        let width = self.width / count as f32;
+        for (i, view) in self.views.iter_mut().enumerate() {
+            let x = self.x + i as f32 * width;
+            view.set_position(x, self.y);
+            view.set_max_width(if count > 1 { width - 10.0 } else { width });
+            self.labels[i].set_position(x, self.y + self.card_height + 40.0);
+        }
+    }
+
+    /// Where a hand's cards are, None for a hand the seat does not have
+    pub fn hand_bounds(&self, hand: usize) -> Option<Rect> {
+        self.views.get(hand).map(|view| view.bounds())
+    }
+
+    pub fn draw(&self) {
+        for view in &self.views {
+            view.draw();
+        }
+        self.name.draw();
+        for label in &self.labels {
+            label.draw();
+        }
+    }
+}
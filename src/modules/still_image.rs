@@ -62,15 +62,26 @@ Usage examples:
 6. Draw the image in your game loop:
     img.draw();
 
+7. Animate it (see the tween module), the image keeps moving on its own while you draw it:
+    img.animate_position(Tween::new(img.pos(), vec2(400.0, 300.0), 0.5, Easing::EaseOut));
+    img.animate_scale(Tween::new(vec2(1.0, 1.0), vec2(1.5, 1.5), 0.3, Easing::EaseOut));
+    img.animate_rotation(Tween::new(0.0, std::f32::consts::PI, 0.3, Easing::Linear));
+    // Turn it over: the width shrinks to nothing, the new texture shows as it grows back
+    img.flip_to(texture_manager.get_preload("assets/ah.png").unwrap(), 0.4);
+    if img.is_animating() { ... }
+
 Additional functionality:
 - Zoom controls: set_zoom(), zoom_in(), zoom_out(), reset_zoom()
 - Stretch controls: enable_stretch(), disable_stretch(), toggle_stretch()
 - Position control: set_position()
+- Scale and rotation: set_scale(), set_rotation(), both about the middle of the image
 - Check if empty: is_empty()
 */
 use macroquad::prelude::*;
 use macroquad::texture::Texture2D;
 
+use crate::modules::tween::{Easing, Tween};
+
 pub struct StillImage {
     texture: Texture2D,
     x: f32,
@@ -81,6 +92,13 @@ pub struct StillImage {
     stretch_enabled: bool, // Flag to control image stretching
     zoom_level: f32, // Zoom factor to scale the image
     filename: String, // Store the original filename/path
+    scale: Vec2, // Scale about the middle of the image, on top of the zoom
+    rotation: f32, // Radians, about the middle of the image
+    move_tween: Option<Tween<Vec2>>, // Where the image is drawn while it slides to x, y
+    scale_tween: Option<Tween<Vec2>>,
+    rotation_tween: Option<Tween<f32>>,
+    flip_tween: Option<Tween<f32>>, // Runs from -1 to 1, the old texture shows while it is below 0
+    flip_from: Option<Texture2D>, // The texture being turned over
 }
 
 impl StillImage {
@@ -110,6 +128,13 @@ impl StillImage {
                 stretch_enabled,
                 zoom_level: zoom_level.max(0.1), // Ensure minimum zoom
                 filename: "__empty__".to_string(), // Use a special filename
+                scale: vec2(1.0, 1.0),
+                rotation: 0.0,
+                move_tween: None,
+                scale_tween: None,
+                rotation_tween: None,
+                flip_tween: None,
+                flip_from: None,
             };
         }
         
@@ -125,6 +150,13 @@ impl StillImage {
             stretch_enabled,
             zoom_level: zoom_level.max(0.1), // Ensure minimum zoom
             filename: asset_path.to_string(), // Store the original filename
+            scale: vec2(1.0, 1.0),
+            rotation: 0.0,
+            move_tween: None,
+            scale_tween: None,
+            rotation_tween: None,
+            flip_tween: None,
+            flip_from: None,
         }
     }
 
@@ -141,19 +173,114 @@ impl StillImage {
         // Apply zoom factor
         let final_width = draw_width * self.zoom_level;
         let final_height = draw_height * self.zoom_level;
-        
+
+        // Animations that are running win over the settled values
+        let pos = self.move_tween.map_or(vec2(self.x, self.y), |tween| tween.value());
+        let mut scale = self.scale_tween.map_or(self.scale, |tween| tween.value());
+        let rotation = self.rotation_tween.map_or(self.rotation, |tween| tween.value());
+        let mut texture = &self.texture;
+        if let Some(flip) = self.flip_tween {
+            let turn = flip.value();
+            scale.x *= turn.abs();
+            if turn < 0.0 {
+                if let Some(from) = &self.flip_from {
+                    texture = from;
+                }
+            }
+        }
+
+        // Scaled about the middle, so the image stays where it was put
+        let scaled = vec2(final_width * scale.x, final_height * scale.y);
         draw_texture_ex(
-            &self.texture,
-            self.x,
-            self.y,
+            texture,
+            pos.x + (final_width - scaled.x) / 2.0,
+            pos.y + (final_height - scaled.y) / 2.0,
             WHITE,
             DrawTextureParams {
-                dest_size: Some(vec2(final_width, final_height)),
+                dest_size: Some(scaled),
+                rotation,
                 ..Default::default()
             },
         );
     }
 
+    // Slides the image, it settles at the tween's end position
+    #[allow(unused)]
+    pub fn animate_position(&mut self, tween: Tween<Vec2>) {
+        let to = tween.target();
+        self.x = to.x;
+        self.y = to.y;
+        self.move_tween = Some(tween);
+    }
+
+    #[allow(unused)]
+    pub fn animate_scale(&mut self, tween: Tween<Vec2>) {
+        self.scale = tween.target();
+        self.scale_tween = Some(tween);
+    }
+
+    #[allow(unused)]
+    pub fn animate_rotation(&mut self, tween: Tween<f32>) {
+        self.rotation = tween.target();
+        self.rotation_tween = Some(tween);
+    }
+
+    // Turns the image over to a preloaded texture, squeezing the width through zero
+    #[allow(unused)]
+    pub fn flip_to(&mut self, preloaded: (Texture2D, Option<Vec<u8>>, String), duration: f32) {
+        let from = self.texture.clone();
+        self.set_preload(preloaded);
+        self.flip_from = Some(from);
+        self.flip_tween = Some(Tween::new(-1.0, 1.0, duration, Easing::Linear));
+    }
+
+    // True while any animation is still playing, delays included
+    #[allow(unused)]
+    pub fn is_animating(&self) -> bool {
+        self.move_tween.is_some_and(|tween| !tween.is_finished())
+            || self.scale_tween.is_some_and(|tween| !tween.is_finished())
+            || self.rotation_tween.is_some_and(|tween| !tween.is_finished())
+            || self.flip_tween.is_some_and(|tween| !tween.is_finished())
+    }
+
+    // Where the image is drawn right now, pos() is where it will settle
+    #[allow(unused)]
+    pub fn drawn_pos(&self) -> Vec2 {
+        self.move_tween.map_or(vec2(self.x, self.y), |tween| tween.value())
+    }
+
+    // Jumps every animation to its end
+    #[allow(unused)]
+    pub fn stop_animation(&mut self) {
+        self.move_tween = None;
+        self.scale_tween = None;
+        self.rotation_tween = None;
+        self.flip_tween = None;
+        self.flip_from = None;
+    }
+
+    #[allow(unused)]
+    pub fn set_scale(&mut self, scale: Vec2) {
+        self.scale = scale;
+        self.scale_tween = None;
+    }
+
+    #[allow(unused)]
+    pub fn get_scale(&self) -> Vec2 {
+        self.scale
+    }
+
+    #[allow(unused)]
+    pub fn set_rotation(&mut self, rotation: f32) {
+        self.rotation = rotation;
+        self.rotation_tween = None;
+    }
+
+    #[allow(unused)]
+    pub fn get_rotation(&self) -> f32 {
+        self.rotation
+    }
+
     // Accessors for image properties
     #[allow(unused)]
     pub fn pos(&self) -> Vec2 {
@@ -171,6 +298,7 @@ impl StillImage {
     pub fn set_position(&mut self, pos: Vec2) {
         self.x = pos[0];
         self.y = pos[1];
+        self.move_tween = None; // A slide in progress would put it somewhere else
     }
 
     // Get and set x position
@@ -182,6 +310,7 @@ impl StillImage {
     #[allow(unused)]
     pub fn set_x(&mut self, x: f32) {
         self.x = x;
+        self.move_tween = None;
     }
 
     // Get and set y position
@@ -193,6 +322,7 @@ impl StillImage {
     #[allow(unused)]
     pub fn set_y(&mut self, y: f32) {
         self.y = y;
+        self.move_tween = None;
     }
 
     // Get the original filename/path of the loaded image
@@ -292,6 +422,9 @@ impl StillImage {
         self.texture = texture;
         self.transparency_mask = mask;
         self.filename = filename;
+        // A flip in progress would still show the old texture
+        self.flip_tween = None;
+        self.flip_from = None;
     }
 
     /// Clears the image by setting it to a 1x1 transparent pixel
@@ -343,7 +476,7 @@ async fn generate_mask(texture_path: &str, width: usize, height: usize) -> Optio
         return None;
     }
  // Only create the mask if we know the image has transparency
- let mut mask = vec![0; (width * height + 7) / 8]; // Create a bitmask with enough bytes
+ let mut mask = vec![0; (width * height).div_ceil(8)]; // Create a bitmask with enough bytes
     // Otherwise, create the transparency mask
     for y in 0..height {
         for x in 0..width {
@@ -371,6 +504,6 @@ pub async fn set_texture_main(texture_path: &str) -> (Texture2D, Option<Vec<u8>>
     let tex_width = texture.width() as usize;
     let tex_height = texture.height() as usize;
     let transparency_mask = generate_mask(texture_path, tex_width, tex_height).await;
-    return (texture, transparency_mask);
+    (texture, transparency_mask)
 }
 
@@ -53,6 +53,13 @@ Where the first two values are x and y positions, and the optional values are fo
 To change the button's text:
     btn_text.set_text("New Text");
 
+To let a key press the button as well (only while the button is enabled):
+    btn_text.activate_on_key(KeyCode::H);
+The key is shown as a hint in the bottom right corner of the button. Call it again
+to add more keys, or clear_keys() to take them all off. To ignore the keys for a
+while, like when a text box is being typed in:
+    btn_text.keys_enabled = false;
+
 Then in the loop you would use:
 if btn_text.click() {
 
@@ -90,9 +97,12 @@ pub struct TextButton {
     cached_text_position: Vec2,
     cached_rect: Rect,
     pub visuable: bool,
+    keys: Vec<KeyCode>, // Keys that press the button too
+    pub keys_enabled: bool, // The keys only press the button while this is true
 }
 
 impl TextButton {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(x: f32, y: f32, width: f32, height: f32, text: impl Into<String>, normal_color: Color, hover_color: Color, font_size: u16) -> Self {
         let enabled = true;
         let off_color = lerp_color(normal_color, GRAY, 0.5);
@@ -129,9 +139,27 @@ impl TextButton {
             cached_text_position,
             cached_rect,
             visuable: true,
+            keys: Vec::new(),
+            keys_enabled: true,
         }
     }
 
+    // Method to let a key press the button, the key is shown on the button
+    #[allow(unused)]
+    pub fn activate_on_key(&mut self, key: KeyCode) -> &mut Self {
+        if !self.keys.contains(&key) {
+            self.keys.push(key);
+        }
+        self
+    }
+
+    // Takes off every key added with activate_on_key
+    #[allow(unused)]
+    pub fn clear_keys(&mut self) -> &mut Self {
+        self.keys.clear();
+        self
+    }
+
     // Method to set custom font - taking Font by value since it implements Clone
     #[allow(unused)]
     pub fn with_font(&mut self, font: Font) -> &mut Self {
@@ -347,8 +375,23 @@ impl TextButton {
             }
         }
 
-        // After drawing, check if the button was clicked
-        is_hovered && self.enabled && is_mouse_button_pressed(MouseButton::Left)
+        // Key hint in the bottom right corner, "Space/Enter" for more than one key
+        if !self.keys.is_empty() {
+            let hint = self.keys.iter().map(|key| format!("{:?}", key)).collect::<Vec<_>>().join("/");
+            let hint_size = 14;
+            let hint_width = measure_text(&hint, None, hint_size, 1.0).width;
+            draw_text(
+                &hint,
+                self.x + self.width - hint_width - self.corner_radius.max(4.0) / 2.0 - 2.0,
+                self.y + self.height - 4.0,
+                hint_size as f32,
+                Color::new(current_text_color.r, current_text_color.g, current_text_color.b, current_text_color.a * 0.7),
+            );
+        }
+
+        // After drawing, check if the button was clicked, or one of its keys was pressed
+        let key_pressed = self.keys_enabled && self.keys.iter().any(|key| is_key_pressed(*key));
+        self.enabled && ((is_hovered && is_mouse_button_pressed(MouseButton::Left)) || key_pressed)
     }
 }
 
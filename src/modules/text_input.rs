@@ -490,7 +490,7 @@ impl TextInput {
                 self.last_key = Some(KeyCode::Delete);
                 self.key_repeat_timer = 0.0;
             } else if key_backspace_pressed && self.cursor_index > 0 {
-                if let Some((prev_offset, _c)) = self.text[..self.cursor_index].char_indices().rev().next() {
+                if let Some((prev_offset, _c)) = self.text[..self.cursor_index].char_indices().next_back() {
                     self.text.replace_range(prev_offset..self.cursor_index, "");
                     self.cursor_index = prev_offset;
                 }
@@ -516,6 +516,7 @@ impl TextInput {
                     self.key_repeat_timer += get_frame_time();
                     if self.key_repeat_timer >= self.key_repeat_delay {
                         self.key_repeat_timer -= self.key_repeat_rate;
+                        #[allow(clippy::collapsible_match)]
                         match key {
                             KeyCode::Left => {
                                 if self.cursor_index > 0 {
@@ -541,7 +542,7 @@ impl TextInput {
                             }
                             KeyCode::Backspace => {
                                 if self.cursor_index > 0 {
-                                    if let Some((prev_offset, _c)) = self.text[..self.cursor_index].char_indices().rev().next() {
+                                    if let Some((prev_offset, _c)) = self.text[..self.cursor_index].char_indices().next_back() {
                                         self.text.replace_range(prev_offset..self.cursor_index, "");
                                         self.cursor_index = prev_offset;
                                     }
@@ -0,0 +1,117 @@
+/*
+Program Details: Tweens, values that move from one number (or point) to another over time
+
+To import you need:
+
+In your mod.rs file located in the modules folder add the following to the end of the file
+    pub mod tween;
+
+Then add the following with the use commands:
+use crate::modules::tween::{Easing, Tween};
+
+Usage examples:
+1. Slide something across the screen in half a second:
+    let slide = Tween::new(
+        vec2(900.0, 20.0),      // where it starts
+        vec2(125.0, 200.0),     // where it ends up
+        0.5,                    // seconds
+        Easing::EaseOut,
+    );
+
+2. Wait a moment before it starts moving, handy for dealing cards one after another:
+    let slide = Tween::new(vec2(900.0, 20.0), vec2(125.0, 200.0), 0.5, Easing::EaseOut).with_delay(0.25);
+
+3. In your game loop ask where it is now:
+    let pos = slide.value();        // the start value until the delay is over, the end value once it is done
+    if slide.is_finished() { ... }
+
+Tweens work on f32 and Vec2, anything that implements Lerp. They read the
+clock themselves (get_time()), so there is nothing to update each frame.
+*/
+use macroquad::prelude::*;
+
+/// How a tween speeds up and slows down
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    /// The same speed all the way
+    Linear,
+    /// Starts fast and slows down at the end, like a card sliding to a stop
+    EaseOut,
+}
+
+impl Easing {
+    /// Eased progress for progress `t` from 0.0 to 1.0
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+        }
+    }
+}
+
+/// Values a tween can move between
+pub trait Lerp: Copy {
+    fn lerp(self, to: Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        self + (to - self) * t
+    }
+}
+
+impl Lerp for Vec2 {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        self + (to - self) * t
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Tween<T: Lerp> {
+    from: T,
+    to: T,
+    start: f64,    // get_time() when it starts moving, after the delay
+    duration: f64, // Seconds
+    easing: Easing,
+}
+
+impl<T: Lerp> Tween<T> {
+    pub fn new(from: T, to: T, duration: f32, easing: Easing) -> Self {
+        Self {
+            from,
+            to,
+            start: get_time(),
+            duration: duration.max(0.0) as f64,
+            easing,
+        }
+    }
+
+    /// Holds the tween at its start value for `delay` seconds first
+    pub fn with_delay(mut self, delay: f32) -> Self {
+        self.start = get_time() + delay.max(0.0) as f64;
+        self
+    }
+
+    // Progress from 0.0 to 1.0, before easing
+    fn progress(&self) -> f32 {
+        if self.duration <= 0.0 {
+            return 1.0;
+        }
+        ((get_time() - self.start) / self.duration).clamp(0.0, 1.0) as f32
+    }
+
+    /// Where the tween is right now
+    pub fn value(&self) -> T {
+        self.from.lerp(self.to, self.easing.apply(self.progress()))
+    }
+
+    /// Where the tween ends up
+    pub fn target(&self) -> T {
+        self.to
+    }
+
+    pub fn is_finished(&self) -> bool {
+        get_time() >= self.start + self.duration
+    }
+}